@@ -1,6 +1,4 @@
-use std::net::SocketAddr;
-
-use myth::{header::HeaderValue, method::Method, uri::Uri, Filter};
+use myth::{header::HeaderValue, method::Method, uri::Uri, Filter, PeerAddr};
 
 #[tokio::main]
 async fn main() {
@@ -15,7 +13,7 @@ async fn main() {
 
     async fn handler(
         uri: &Uri,
-        remote_addr: SocketAddr,
+        remote_addr: PeerAddr,
         method: &Method,
         cookie: Option<&HeaderValue>,
     ) -> myth::Result<String> {
@@ -1,18 +1,90 @@
-//! Remote [`SocketAddr`]s
+//! Remote addresses
 
-use std::{fmt, net::SocketAddr};
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    path::PathBuf,
+};
 
 use crate::{filter::ready::ready_filter, impl_Filter, outcome::Outcome};
 
-/// Creates a [`Filter`](crate) that extracts the remote [`SocketAddr`] of the client connecting to
+/// The address of a peer connected to the server.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PeerAddr {
+    /// A TCP peer.
+    Tcp(SocketAddr),
+
+    /// A Unix domain socket peer, with its path if the peer socket was bound to one.
+    Unix(Option<PathBuf>),
+
+    /// A UDP peer, such as an HTTP/3 client.
+    Udp(SocketAddr),
+}
+
+impl PeerAddr {
+    /// Returns the [`SocketAddr`] of a TCP peer.
+    pub fn tcp(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(addr) => Some(*addr),
+            Self::Unix(_) | Self::Udp(_) => None,
+        }
+    }
+
+    /// Returns the [`IpAddr`] of a TCP or UDP peer.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Tcp(addr) | Self::Udp(addr) => Some(addr.ip()),
+            Self::Unix(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) | Self::Udp(addr) => addr.fmt(f),
+            Self::Unix(Some(path)) => write!(f, "unix:{}", path.display()),
+            Self::Unix(None) => write!(f, "unix:(unnamed)"),
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl From<SocketAddrV4> for PeerAddr {
+    fn from(addr: SocketAddrV4) -> Self {
+        Self::Tcp(addr.into())
+    }
+}
+
+impl From<SocketAddrV6> for PeerAddr {
+    fn from(addr: SocketAddrV6) -> Self {
+        Self::Tcp(addr.into())
+    }
+}
+
+impl<I: Into<IpAddr>> From<(I, u16)> for PeerAddr {
+    fn from(addr: (I, u16)) -> Self {
+        Self::Tcp(addr.into())
+    }
+}
+
+/// Creates a [`Filter`](crate) that extracts the remote [`PeerAddr`] of the client connecting to
 /// the server.
-pub fn remote_addr() -> impl_Filter!(SocketAddr => Copy + (fmt::Debug)) {
-    ready_filter(|request, _| Outcome::Success((request.remote_addr,)))
+pub fn remote_addr() -> impl_Filter!(PeerAddr => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| Outcome::Success((request.connection.remote_addr.clone(),)))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::remote_addr;
+    use std::path::PathBuf;
+
+    use super::{remote_addr, PeerAddr};
     use crate::test;
 
     #[tokio::test]
@@ -20,8 +92,27 @@ mod tests {
         test::get()
             .remote_addr(([127, 0, 0, 1], 12345))
             .success(&remote_addr(), |addr| {
-                assert_eq!(addr, "127.0.0.1:12345".parse().unwrap());
+                assert_eq!(addr, PeerAddr::Tcp("127.0.0.1:12345".parse().unwrap()));
             })
             .await;
     }
+
+    #[tokio::test]
+    async fn extract_unix_addr() {
+        test::get()
+            .remote_addr(PeerAddr::Unix(None))
+            .success(&remote_addr(), |addr: PeerAddr| {
+                assert_eq!(addr.tcp(), None);
+                assert_eq!(addr.to_string(), "unix:(unnamed)");
+            })
+            .await;
+    }
+
+    #[test]
+    fn display() {
+        let addr = PeerAddr::from(([10, 0, 0, 1], 80));
+        assert_eq!(addr.to_string(), "10.0.0.1:80");
+        let addr = PeerAddr::Unix(Some(PathBuf::from("/run/myth.sock")));
+        assert_eq!(addr.to_string(), "unix:/run/myth.sock");
+    }
 }
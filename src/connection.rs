@@ -0,0 +1,134 @@
+//! Information about the connection that a request was received on.
+//!
+//! The [`info()`] filter extracts a [`ConnectionInfo`]. Its addresses and TLS session are
+//! captured once when the connection is accepted, and shared by every request on it.
+//!
+//! # Example
+//!
+//! ```
+//! use myth::{connection::ConnectionInfo, Filter};
+//!
+//! let filter = myth::connection::info().handle(|info: ConnectionInfo| async move {
+//!     Ok(format!(
+//!         "Connection {} from {} over {:?}",
+//!         info.id(),
+//!         info.remote_addr(),
+//!         info.http_version(),
+//!     ))
+//! });
+//! ```
+
+use std::fmt;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+
+use crate::{
+    filter::ready::ready_filter, impl_Filter, outcome::Outcome, version::Version, PeerAddr,
+};
+
+/// Information about the connection that a request was received on, extracted by [`info()`].
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    id: u64,
+    local_addr: Option<PeerAddr>,
+    remote_addr: PeerAddr,
+    http_version: Version,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<crate::tls::TlsInfo>>,
+}
+
+impl ConnectionInfo {
+    /// Returns an identifier for the connection, which is unique within the process.
+    ///
+    /// Requests with the same identifier were received on the same connection.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the local address that the client connected to, if it is known.
+    ///
+    /// With the [PROXY protocol](crate::proxy), this is the destination address in the header.
+    pub fn local_addr(&self) -> Option<&PeerAddr> {
+        self.local_addr.as_ref()
+    }
+
+    /// Returns the remote address of the client, as also extracted by
+    /// [`remote_addr()`](crate::remote_addr).
+    pub fn remote_addr(&self) -> &PeerAddr {
+        &self.remote_addr
+    }
+
+    /// Returns the HTTP version of the request.
+    pub fn http_version(&self) -> Version {
+        self.http_version
+    }
+
+    /// Returns whether the connection uses TLS.
+    pub fn is_tls(&self) -> bool {
+        #[cfg(feature = "tls")]
+        return self.tls.is_some();
+        #[cfg(not(feature = "tls"))]
+        return false;
+    }
+
+    /// Returns information about the TLS session of the connection, including the negotiated
+    /// protocol version, cipher suite, ALPN protocol, and server name, or [`None`] if the
+    /// connection does not use TLS.
+    #[cfg(feature = "tls")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "tls")))]
+    pub fn tls(&self) -> Option<&crate::tls::TlsInfo> {
+        self.tls.as_deref()
+    }
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts [`ConnectionInfo`] about the connection
+/// that the request was received on.
+pub fn info() -> impl_Filter!(ConnectionInfo => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| {
+        let connection = &request.connection;
+        let info = ConnectionInfo {
+            id: connection.id,
+            local_addr: connection.local_addr.clone(),
+            remote_addr: connection.remote_addr.clone(),
+            http_version: request.version,
+            #[cfg(feature = "tls")]
+            tls: connection.tls.clone(),
+        };
+        Outcome::Success((info,))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{info, ConnectionInfo};
+    use crate::{test, version::Version, PeerAddr};
+
+    #[tokio::test]
+    async fn extract_info() {
+        test::get()
+            .version(Version::HTTP_2)
+            .local_addr(([127, 0, 0, 1], 8080))
+            .remote_addr(([192, 0, 2, 1], 12345))
+            .success(&info(), |info: ConnectionInfo| {
+                assert_eq!(
+                    info.local_addr(),
+                    Some(&PeerAddr::from(([127, 0, 0, 1], 8080)))
+                );
+                assert_eq!(info.remote_addr(), &PeerAddr::from(([192, 0, 2, 1], 12345)));
+                assert_eq!(info.http_version(), Version::HTTP_2);
+                assert!(!info.is_tls());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn unique_ids() {
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            test::get()
+                .success(&info(), |info: ConnectionInfo| ids.push(info.id()))
+                .await;
+        }
+        assert_ne!(ids[0], ids[1]);
+    }
+}
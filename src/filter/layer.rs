@@ -0,0 +1,95 @@
+use std::{fmt, future::Future, pin::Pin};
+
+use hyper::body::HttpBody;
+use tower_service::Service;
+
+use super::{FilterBase, FilterExecute, FilterSealed};
+use crate::{
+    outcome::{Outcome, RequestOutcome},
+    request::{HyperRequest, Mounted, Request, RequestState},
+    service::tower::{call, into_hyper, Forwarded},
+    Response,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A [`Filter`](crate::Filter) wrapped in a Tower layer.
+///
+/// This is created by the [`layer`](crate::Filter::layer) method on [`Filter`](crate::Filter).
+#[derive(Clone)]
+pub struct Layered<S> {
+    pub(super) service: S,
+}
+
+impl<S> fmt::Debug for Layered<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Layered").finish_non_exhaustive()
+    }
+}
+
+impl<S> FilterSealed for Layered<S> {}
+
+impl<'f, S> FilterBase<'f> for Layered<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Input = ();
+
+    type Success = (Response,);
+}
+
+impl<'f, S, B> FilterExecute<'f> for Layered<S>
+where
+    S: Service<HyperRequest, Response = hyper::Response<B>> + Clone + Send + Sync + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Future = Pin<Box<dyn Future<Output = RequestOutcome<(), (Response,)>> + Send + 'f>>;
+
+    fn execute(
+        &'f self,
+        request: &'f Request,
+        request_state: RequestState,
+        (): Self::Input,
+    ) -> Self::Future {
+        let mounted = Mounted {
+            connection: request.connection.clone(),
+            current_path_index: request_state.current_path_index,
+            origin: request_state.origin.clone(),
+            params: request_state.params.clone(),
+            url_for: request_state.url_for.clone(),
+        };
+        let (mut hyper_request, request_state) =
+            into_hyper(request, request_state, request.uri.clone());
+        hyper_request.extensions_mut().insert(mounted);
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            match call(service, hyper_request).await {
+                Ok(mut response) => match response.extensions_mut().remove::<Forwarded>() {
+                    Some(Forwarded {
+                        forwarding,
+                        request_state,
+                    }) => RequestOutcome {
+                        request_state,
+                        outcome: Outcome::Forward {
+                            input: (),
+                            forwarding,
+                        },
+                    },
+                    None => RequestOutcome {
+                        request_state,
+                        outcome: Outcome::Success((response,)),
+                    },
+                },
+                Err(error) => RequestOutcome {
+                    request_state,
+                    outcome: Outcome::Error(error),
+                },
+            }
+        })
+    }
+}
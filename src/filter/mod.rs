@@ -1,6 +1,8 @@
 mod and;
 mod dynamic;
 mod handle;
+#[cfg(feature = "tower")]
+mod layer;
 mod or;
 pub(crate) mod ready;
 mod receive;
@@ -14,10 +16,14 @@ use std::{future::Future, sync::Arc};
 use unused::Unused;
 
 pub use self::dynamic::DynamicFilter;
+#[cfg(feature = "tower")]
+pub use self::layer::Layered;
 use self::{
     and::And, dynamic::BoxedFutureFilter, handle::Handle, or::Or, receive::Receive,
     recover::Recover, recover_forward::RecoverForward, then::Then, untuple::Untuple,
 };
+#[cfg(feature = "tower")]
+use crate::service::FilterService;
 use crate::{
     generics::tuples::Tuple,
     outcome::RequestOutcome,
@@ -177,6 +183,68 @@ pub trait Filter:
     {
         DynamicFilter(Arc::new(BoxedFutureFilter(self)))
     }
+
+    /// Converts this [`Filter`] into a [`Service`](tower_service::Service), so that it can be
+    /// wrapped in Tower middleware or served by other servers.
+    ///
+    /// Only works if [`Self::Input`](FilterBase::Input) is `()` and
+    /// [`Self::Success`](FilterBase::Success) is a single [`Responder`](crate::Responder).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use myth::Filter;
+    ///
+    /// let service = myth::any()
+    ///     .handle(|| async { Ok("Hello world!") })
+    ///     .into_service();
+    /// ```
+    #[cfg(feature = "tower")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "tower")))]
+    fn into_service(self) -> FilterService<Self>
+    where
+        Self: Sized,
+    {
+        FilterService::new(self)
+    }
+
+    /// Wraps this [`Filter`] in a Tower [`Layer`](tower_layer::Layer), such as a timeout or
+    /// load shedding, which then applies only to requests that reach this [`Filter`].
+    ///
+    /// Only works if this [`Filter`] can be converted into a [service](Self::into_service).
+    /// The result is a [`Layered`](crate::service::Layered), which succeeds with a
+    /// [`Response`](crate::Response). Forwarding passes through the
+    /// layer, so that another [`Filter`] combined with [`or`](Self::or) can still handle the
+    /// request and its body. Errors of the layer become a
+    /// [`ServiceError`](crate::service::ServiceError).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use myth::Filter;
+    /// use tower::timeout::TimeoutLayer;
+    ///
+    /// let slow = myth::path::literal("slow")
+    ///     .handle(|| async { Ok("Finally!") })
+    ///     .layer(TimeoutLayer::new(Duration::from_secs(30)));
+    /// let filter = slow.or(myth::any().handle(|| async {
+    ///     Ok(myth::Responder::into_response("Hello world!"))
+    /// }));
+    /// ```
+    #[cfg(feature = "tower")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "tower")))]
+    fn layer<L>(self, layer: L) -> Layered<L::Service>
+    where
+        Self: Sized,
+        L: tower_layer::Layer<FilterService<Self>>,
+        Layered<L::Service>: Filter,
+    {
+        Layered {
+            service: layer.layer(self.into_service()),
+        }
+    }
 }
 
 impl<T: ?Sized> Filter for T where T: for<'f> FilterExecute<'f> {}
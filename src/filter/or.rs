@@ -11,6 +11,7 @@ use super::{FilterExecute, FilterSealed, RequestOutcome};
 use crate::{
     outcome::Outcome,
     request::{Request, RequestState},
+    url::UrlFor,
     FilterBase, Forwarding,
 };
 
@@ -46,6 +47,9 @@ where
         input: Self::Input,
     ) -> Self::Future {
         let path_index = request_state.current_path_index;
+        let params_len = request_state.params.len();
+        let origin = request_state.origin.clone();
+        let url_for = request_state.url_for.clone();
         OrFuture {
             state: OrFutureState::First {
                 future: self.first.execute(request, request_state, input),
@@ -53,6 +57,9 @@ where
                 request,
             },
             path_index,
+            params_len,
+            origin,
+            url_for,
         }
     }
 }
@@ -66,6 +73,9 @@ pin_project! {
         #[pin]
         state: OrFutureState<'f, A, B>,
         path_index: usize,
+        params_len: usize,
+        origin: Option<String>,
+        url_for: Option<UrlFor>,
     }
 }
 
@@ -120,6 +130,9 @@ where
                     }),
                     Outcome::Forward { input, forwarding } => {
                         request_state.current_path_index = *proj.path_index;
+                        request_state.params.truncate(*proj.params_len);
+                        request_state.origin = proj.origin.clone();
+                        request_state.url_for = proj.url_for.clone();
                         let state = OrFutureState::Second {
                             future: second.execute(request, request_state, input),
                             first_forwarding: Some(forwarding),
@@ -141,6 +154,9 @@ where
                     outcome @ (Outcome::Success(_) | Outcome::Error(_)) => outcome,
                     Outcome::Forward { input, forwarding } => {
                         request_state.current_path_index = *proj.path_index;
+                        request_state.params.truncate(*proj.params_len);
+                        request_state.origin = proj.origin.take();
+                        request_state.url_for = proj.url_for.take();
                         Outcome::Forward {
                             input,
                             forwarding: first_forwarding.take().unwrap().combine(forwarding),
@@ -6,7 +6,7 @@ use crate::{
 };
 
 /// Provides data about [`Filter`](crate::Filter)s that fail to match
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[non_exhaustive]
 pub enum Forwarding {
     /// Represents a resource not being found.
@@ -62,6 +62,20 @@ impl AttemptedMethods {
     pub const PATCH: Self = Self(1 << 7);
     pub const TRACE: Self = Self(1 << 8);
 
+    /// Returns the attempted method for `method`, which is [`NONE`](Self::NONE) for extension
+    /// methods.
+    pub(crate) fn from_method(method: &Method) -> Self {
+        macro_rules! match_method {
+            ($($method:ident)*) => {
+                $(if method == Method::$method {
+                    return Self::$method;
+                })*
+            };
+        }
+        match_method!(GET POST PUT DELETE HEAD OPTIONS CONNECT PATCH TRACE);
+        Self::NONE
+    }
+
     fn into_header_value(self) -> HeaderValue {
         let mut string = String::with_capacity(10);
         macro_rules! check_method {
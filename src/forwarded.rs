@@ -0,0 +1,392 @@
+//! The address, scheme, and host of clients behind reverse proxies.
+//!
+//! Behind a reverse proxy, [`remote_addr()`](crate::remote_addr) returns the address of the
+//! proxy. The [`client_ip()`] filter instead reads the `Forwarded`, `X-Forwarded-For`, and
+//! `X-Real-IP` headers that trusted proxies add.
+//!
+//! # Example
+//!
+//! ```
+//! use myth::{
+//!     forwarded::{client_ip, ClientInfo, ForwardedConfig},
+//!     Filter,
+//! };
+//!
+//! let config = ForwardedConfig::new().trusted(["10.0.0.0/8".parse().unwrap()]);
+//! let filter = client_ip(config).handle(|client: ClientInfo| async move {
+//!     Ok(format!("Hello, {:?}!", client.ip()))
+//! });
+//! ```
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
+
+use crate::{
+    filter::ready::ready_filter,
+    header::{self, HeaderName},
+    impl_Filter,
+    outcome::Outcome,
+    request::Request,
+    IpNet, PeerAddr,
+};
+
+static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+static X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
+static X_FORWARDED_HOST: HeaderName = HeaderName::from_static("x-forwarded-host");
+static X_REAL_IP: HeaderName = HeaderName::from_static("x-real-ip");
+
+/// A configuration for [`client_ip()`], which lists the proxies whose headers are trusted.
+#[derive(Clone, Debug, Default)]
+pub struct ForwardedConfig {
+    trusted: Vec<IpNet>,
+}
+
+impl ForwardedConfig {
+    /// Creates a new configuration that does not trust any proxies.
+    ///
+    /// Peers connected through a Unix domain socket are always trusted, since only local
+    /// processes can connect to them.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts the headers added by proxies whose address is within one of `proxies`.
+    pub fn trusted(mut self, proxies: impl IntoIterator<Item = IpNet>) -> Self {
+        self.trusted.extend(proxies);
+        self
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        // IPv4 peers of dual-stack listeners have IPv4-mapped IPv6 addresses.
+        let ip = ip.to_canonical();
+        self.trusted.iter().any(|net| net.contains(&ip))
+    }
+
+    fn is_trusted_peer(&self, addr: &PeerAddr) -> bool {
+        match addr.ip() {
+            Some(ip) => self.is_trusted(ip),
+            None => true,
+        }
+    }
+}
+
+/// Information about a client, extracted by [`client_ip()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientInfo {
+    ip: Option<IpAddr>,
+    scheme: Option<String>,
+    host: Option<String>,
+}
+
+impl ClientInfo {
+    /// Returns the IP address of the client.
+    ///
+    /// This is [`None`] if the client connected through a Unix domain socket without passing
+    /// on an address, or if the trusted proxies did not report an address that is not trusted.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
+
+    /// Returns the scheme that the client used, such as `https`, if it is known.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Returns the host that the client requested, if it is known.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// Returns the origin that the client requested, such as `https://example.com`, if both the
+    /// scheme and host are known.
+    pub fn origin(&self) -> Option<String> {
+        match (&self.scheme, &self.host) {
+            (Some(scheme), Some(host)) => Some(format!("{}://{}", scheme, host)),
+            _ => None,
+        }
+    }
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts the [`ClientInfo`] of the client, as
+/// reported by trusted proxies.
+///
+/// Proxies are walked from the closest to the furthest, first through the `Forwarded` header,
+/// and otherwise through the `X-Forwarded-For` header or the `X-Real-IP` header. The first
+/// address that is not trusted is the client. If an address is obfuscated or `unknown`, or every
+/// address is trusted, the client is unknown. Headers are ignored unless the peer connected to
+/// the server is trusted.
+///
+/// The scheme and host come from the `proto` and `host` parameters of `Forwarded`, or the last
+/// value of `X-Forwarded-Proto` and `X-Forwarded-Host`, and otherwise from the request itself.
+/// When both are known, later redirects made by [`path::end()`](crate::path::end) use them to
+/// build absolute URLs.
+pub fn client_ip(config: ForwardedConfig) -> impl_Filter!(ClientInfo => Clone + (fmt::Debug)) {
+    ready_filter(move |request, request_state| {
+        let client = client_info(request, &config);
+        request_state.origin = client.origin();
+        Outcome::Success((client,))
+    })
+}
+
+fn client_info(request: &Request, config: &ForwardedConfig) -> ClientInfo {
+    let peer = &request.connection.remote_addr;
+    let mut client = ClientInfo {
+        ip: peer.ip(),
+        scheme: request.uri.scheme_str().map(str::to_owned),
+        host: request
+            .header(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| request.uri.authority().map(|authority| authority.as_str()))
+            .map(str::to_owned),
+    };
+    if !config.is_trusted_peer(peer) {
+        return client;
+    }
+
+    let forwarded = forwarded_elements(request);
+    if !forwarded.is_empty() {
+        client.ip = None;
+        for element in forwarded.iter().rev() {
+            if let Some(proto) = element.proto {
+                client.scheme = Some(proto.to_ascii_lowercase());
+            }
+            if let Some(host) = element.host {
+                client.host = Some(host.to_owned());
+            }
+            match element.node.and_then(parse_node) {
+                Some(ip) if config.is_trusted(ip) => {}
+                Some(ip) => {
+                    client.ip = Some(ip);
+                    break;
+                }
+                None => break,
+            }
+        }
+        return client;
+    }
+
+    let chain: Vec<&str> = header_values(request, &X_FORWARDED_FOR).collect();
+    let chain = if chain.is_empty() {
+        header_values(request, &X_REAL_IP).collect()
+    } else {
+        chain
+    };
+    if !chain.is_empty() {
+        client.ip = chain
+            .into_iter()
+            .rev()
+            .map(parse_node)
+            .find(|ip| !matches!(ip, Some(ip) if config.is_trusted(*ip)))
+            .flatten();
+    }
+    if let Some(proto) = header_values(request, &X_FORWARDED_PROTO).last() {
+        client.scheme = Some(proto.to_ascii_lowercase());
+    }
+    if let Some(host) = header_values(request, &X_FORWARDED_HOST).last() {
+        client.host = Some(host.to_owned());
+    }
+    client
+}
+
+/// Returns the comma-separated values of every header named `name`, in order.
+fn header_values<'r>(request: &'r Request, name: &'r HeaderName) -> impl Iterator<Item = &'r str> {
+    request
+        .header_all(name)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// An element of a `Forwarded` header, added by a single proxy.
+#[derive(Debug, Default, PartialEq)]
+struct Element<'r> {
+    node: Option<&'r str>,
+    proto: Option<&'r str>,
+    host: Option<&'r str>,
+}
+
+fn forwarded_elements(request: &Request) -> Vec<Element<'_>> {
+    header_values(request, &header::FORWARDED)
+        .map(|element| {
+            let mut parsed = Element::default();
+            for pair in element.split(';') {
+                let (key, value) = match pair.split_once('=') {
+                    Some(pair) => pair,
+                    None => continue,
+                };
+                let value = value.trim().trim_matches('"');
+                match key.trim() {
+                    key if key.eq_ignore_ascii_case("for") => parsed.node = Some(value),
+                    key if key.eq_ignore_ascii_case("proto") => parsed.proto = Some(value),
+                    key if key.eq_ignore_ascii_case("host") => parsed.host = Some(value),
+                    _ => {}
+                }
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Parses a node, which is an IP address with an optional port, or [`None`] if it is obfuscated
+/// or `unknown`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        let (ip, _) = rest.split_once(']')?;
+        return ip.parse().ok();
+    }
+    node.parse()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{client_ip, parse_node, ClientInfo, ForwardedConfig};
+    use crate::{path, test, Filter, PeerAddr};
+
+    fn config() -> ForwardedConfig {
+        ForwardedConfig::new().trusted([
+            "10.0.0.0/8".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ])
+    }
+
+    fn ip(ip: &str) -> Option<IpAddr> {
+        Some(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn nodes() {
+        assert_eq!(parse_node("192.0.2.43"), ip("192.0.2.43"));
+        assert_eq!(parse_node("192.0.2.43:47011"), ip("192.0.2.43"));
+        assert_eq!(
+            parse_node("\"[2001:db8:cafe::17]:4711\""),
+            ip("2001:db8:cafe::17")
+        );
+        assert_eq!(parse_node("2001:db8:cafe::17"), ip("2001:db8:cafe::17"));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    #[tokio::test]
+    async fn forwarded() {
+        test::get()
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header(
+                "Forwarded",
+                "for=198.51.100.7, for=192.0.2.60;proto=HTTPS;host=example.com",
+            )
+            .header("Forwarded", "for=\"[2001:db8::1]:8080\";proto=http")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.60"));
+                assert_eq!(client.scheme(), Some("https"));
+                assert_eq!(client.host(), Some("example.com"));
+                assert_eq!(client.origin().as_deref(), Some("https://example.com"));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn forwarded_unknown() {
+        test::get()
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Forwarded", "for=192.0.2.60, for=unknown, for=10.0.0.3")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), None);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn only_trusted_hops() {
+        test::get()
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("X-Forwarded-For", "10.1.2.3, 10.0.0.3")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), None);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn ipv4_mapped_peer() {
+        let peer: IpAddr = "::ffff:10.0.0.2".parse().unwrap();
+        test::get()
+            .remote_addr((peer, 1234))
+            .header("X-Forwarded-For", "192.0.2.60, ::ffff:10.1.2.3")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.60"));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn x_forwarded_for() {
+        test::get()
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Host", "internal")
+            .header("X-Forwarded-For", "203.0.113.1, 192.0.2.60, 10.1.2.3")
+            .header("X-Forwarded-Proto", "https")
+            .header("X-Forwarded-Host", "example.com")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.60"));
+                assert_eq!(client.scheme(), Some("https"));
+                assert_eq!(client.host(), Some("example.com"));
+            })
+            .await;
+
+        test::get()
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("X-Real-IP", "192.0.2.60")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.60"));
+                assert_eq!(client.scheme(), None);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn untrusted_peer() {
+        test::get()
+            .remote_addr(([192, 0, 2, 1], 1234))
+            .header("Host", "example.com")
+            .header("X-Forwarded-For", "203.0.113.1")
+            .header("X-Forwarded-Host", "evil.example")
+            .success(&client_ip(config()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.1"));
+                assert_eq!(client.host(), Some("example.com"));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn unix_peer() {
+        test::get()
+            .remote_addr(PeerAddr::Unix(None))
+            .header("X-Real-IP", "192.0.2.60")
+            .success(&client_ip(ForwardedConfig::new()), |client: ClientInfo| {
+                assert_eq!(client.ip(), ip("192.0.2.60"));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn absolute_redirect() {
+        let filter = client_ip(config())
+            .and(path::literal("docs"))
+            .and(path::end());
+        let redirect: path::Redirect = test::get()
+            .uri("/docs/")
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Forwarded", "for=192.0.2.60;proto=https;host=example.com")
+            .error(&filter)
+            .await;
+        assert_eq!(redirect.location(), "https://example.com/docs");
+    }
+}
@@ -1,4 +1,6 @@
-#![forbid(unsafe_code)]
+// `forbid` cannot be relaxed for a single module, so the crate root denies unsafe code and every
+// module except `listen_fds`, which adopts socket-activated file descriptors, forbids it.
+#![deny(unsafe_code)]
 #![cfg_attr(myth_docs, feature(doc_cfg, doc_notable_trait))]
 
 //! # Myth
@@ -46,49 +48,106 @@
 //! [`FilterBase::Success`]. Using a [base trait](FilterBase) with a lifetime `'f` means that we
 //! can express data borrowed from other [`Filter`]s, such as `&'f str`.
 
+#[forbid(unsafe_code)]
 #[macro_use]
 mod macros;
 
+#[forbid(unsafe_code)]
 mod addr;
+#[forbid(unsafe_code)]
 mod basic;
+#[forbid(unsafe_code)]
 pub mod body;
+#[forbid(unsafe_code)]
 pub mod cache;
+#[forbid(unsafe_code)]
+pub mod connection;
+#[forbid(unsafe_code)]
 pub mod errors;
+#[forbid(unsafe_code)]
 mod filter;
+#[forbid(unsafe_code)]
 pub mod form;
+#[forbid(unsafe_code)]
 mod forward;
+#[forbid(unsafe_code)]
+pub mod forwarded;
+#[forbid(unsafe_code)]
 pub mod generics;
+#[forbid(unsafe_code)]
 pub mod header;
+#[forbid(unsafe_code)]
 #[cfg(feature = "json")]
 #[cfg_attr(myth_docs, doc(cfg(feature = "json")))]
 pub mod json;
+#[cfg(unix)]
+mod listen_fds;
+#[forbid(unsafe_code)]
 pub mod method;
+#[forbid(unsafe_code)]
 mod outcome;
+#[forbid(unsafe_code)]
 pub mod path;
+#[forbid(unsafe_code)]
+pub mod proxy;
+#[forbid(unsafe_code)]
 pub mod query;
+#[forbid(unsafe_code)]
 mod request;
+#[forbid(unsafe_code)]
 mod response;
+#[forbid(unsafe_code)]
+pub mod router;
+#[forbid(unsafe_code)]
 pub mod security;
-mod server;
+#[forbid(unsafe_code)]
+pub mod server;
+#[forbid(unsafe_code)]
 pub mod service;
+#[forbid(unsafe_code)]
 pub mod test;
+#[forbid(unsafe_code)]
 #[cfg(feature = "tls")]
-mod tls;
+#[cfg_attr(myth_docs, doc(cfg(feature = "tls")))]
+pub mod tls;
+#[forbid(unsafe_code)]
 mod traits;
+#[forbid(unsafe_code)]
+#[cfg(unix)]
+mod unix;
+#[forbid(unsafe_code)]
+pub mod upgrade;
+#[forbid(unsafe_code)]
 pub mod uri;
+#[forbid(unsafe_code)]
+pub mod url;
+#[forbid(unsafe_code)]
 mod util;
+#[forbid(unsafe_code)]
 pub mod version;
+#[forbid(unsafe_code)]
+#[cfg(feature = "websocket")]
+#[cfg_attr(myth_docs, doc(cfg(feature = "websocket")))]
+pub mod ws;
 
 pub use hyper::{body::Bytes, Body, StatusCode};
+pub use ipnet::IpNet;
 
 #[cfg(feature = "tls")]
 pub use self::tls::TlsConfig;
 pub use self::{
-    addr::remote_addr,
+    addr::{remote_addr, PeerAddr},
     basic::{any, borrowing, cloning, never},
     errors::Result,
     filter::{DynamicFilter, Filter, FilterBase},
     forward::Forwarding,
     response::{html, Responder, Response},
-    server::{serve, Server},
+    router::Router,
+    server::{serve, Http1Config, Http2Config, Server},
+    service::ConnectionLimits,
+};
+#[cfg(unix)]
+pub use self::{
+    listen_fds::{listen_fds, ListenFds},
+    unix::UnixConfig,
 };
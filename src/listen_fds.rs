@@ -0,0 +1,269 @@
+use std::{
+    env, io, mem,
+    os::unix::io::{FromRawFd, OwnedFd, RawFd},
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The first file descriptor passed with socket activation.
+const LISTEN_FDS_START: RawFd = 3;
+
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Takes the listening sockets passed to this process with systemd-style socket activation,
+/// through the `LISTEN_PID`, `LISTEN_FDS`, and `LISTEN_FDNAMES` environment variables.
+///
+/// Only the first call returns the sockets; later calls return an empty [`ListenFds`]. If the
+/// variables are not set, or were meant for another process, an empty [`ListenFds`] is also
+/// returned. The environment is left as it is, since changing it is not thread safe, and child
+/// processes ignore the variables because `LISTEN_PID` does not match them. File descriptors
+/// that are not open sockets are logged and skipped, so they cannot be taken.
+///
+/// # Errors
+///
+/// Returns an error if `LISTEN_FDS` is not a valid number.
+///
+/// # Example
+///
+/// ```no_run
+/// use myth::Filter;
+///
+/// # #[tokio::main] async fn main() -> std::io::Result<()> {
+/// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+///
+/// let mut fds = myth::listen_fds()?;
+/// let server = match fds.take_named("http") {
+///     Some(fd) => myth::serve(filter).from_fd(fd),
+///     // Not socket activated, so bind normally.
+///     None => myth::serve(filter).bind(([127, 0, 0, 1], 8080)),
+/// };
+/// server.run().await;
+/// # Ok(()) }
+/// ```
+#[cfg_attr(myth_docs, doc(cfg(unix)))]
+pub fn listen_fds() -> io::Result<ListenFds> {
+    if TAKEN.swap(true, Ordering::AcqRel) {
+        return Ok(ListenFds::default());
+    }
+
+    let pid = env::var("LISTEN_PID").ok();
+    let count = env::var("LISTEN_FDS").ok();
+    let names = env::var("LISTEN_FDNAMES").ok();
+
+    let passed = parse(
+        pid.as_deref(),
+        count.as_deref(),
+        names.as_deref(),
+        process::id(),
+    )?;
+    Ok(ListenFds {
+        fds: adopt_all(passed),
+    })
+}
+
+/// Adopts each passed file descriptor, skipping those that are not open sockets so that the
+/// others keep their indices.
+fn adopt_all(passed: Vec<(RawFd, String)>) -> Vec<Option<(String, OwnedFd)>> {
+    passed
+        .into_iter()
+        .map(|(raw_fd, name)| match adopt(raw_fd) {
+            Ok(fd) => Some((name, fd)),
+            Err(error) => {
+                tracing::warn!("Skipping passed file descriptor {}: {}", raw_fd, error);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parses the values of `LISTEN_PID`, `LISTEN_FDS`, and `LISTEN_FDNAMES`, returning the file
+/// descriptors that were passed to the process `own_pid` and their names.
+fn parse(
+    pid: Option<&str>,
+    count: Option<&str>,
+    names: Option<&str>,
+    own_pid: u32,
+) -> io::Result<Vec<(RawFd, String)>> {
+    let (pid, count) = match (pid, count) {
+        (Some(pid), Some(count)) => (pid, count),
+        _ => return Ok(Vec::new()),
+    };
+    if pid.trim().parse() != Ok(own_pid) {
+        tracing::debug!("Ignoring LISTEN_FDS for process {}", pid);
+        return Ok(Vec::new());
+    }
+    let count: RawFd = count.trim().parse().map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid LISTEN_FDS: {}", error),
+        )
+    })?;
+    let mut names = names.unwrap_or_default().split(':');
+
+    Ok((LISTEN_FDS_START..LISTEN_FDS_START.saturating_add(count))
+        .map(|raw_fd| {
+            let name = match names.next() {
+                Some(name) if !name.is_empty() => name.to_owned(),
+                _ => "unknown".to_owned(),
+            };
+            (raw_fd, name)
+        })
+        .collect())
+}
+
+/// Takes ownership of a passed file descriptor after checking that it is an open socket, and
+/// sets `FD_CLOEXEC` so that it is not leaked into child processes.
+///
+/// File descriptors that are not sockets are left open and not owned.
+#[allow(unsafe_code)]
+fn adopt(raw_fd: RawFd) -> io::Result<OwnedFd> {
+    // SAFETY: `fstat` and `fcntl` only read and set the flags of `raw_fd`, and fail with `EBADF`
+    // if it is not open. It is only owned once it is known to be an open socket, which was passed
+    // to us for this purpose, and `TAKEN` ensures that each one is only ever owned once.
+    unsafe {
+        let mut stat: libc::stat = mem::zeroed();
+        if libc::fstat(raw_fd, &mut stat) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        if stat.st_mode & libc::S_IFMT != libc::S_IFSOCK {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("file descriptor {} is not a socket", raw_fd),
+            ));
+        }
+        let flags = libc::fcntl(raw_fd, libc::F_GETFD);
+        if flags == -1 || libc::fcntl(raw_fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(OwnedFd::from_raw_fd(raw_fd))
+    }
+}
+
+/// Listening sockets passed with socket activation, created by [`listen_fds()`].
+///
+/// Each socket can be taken once, and passed to
+/// [`Server::from_fd()`](crate::Server::from_fd). Sockets that are not taken are closed when
+/// this is dropped.
+#[derive(Debug, Default)]
+#[cfg_attr(myth_docs, doc(cfg(unix)))]
+pub struct ListenFds {
+    fds: Vec<Option<(String, OwnedFd)>>,
+}
+
+impl ListenFds {
+    /// Returns the number of sockets that were passed, including those that have been taken.
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    /// Returns whether no sockets were passed.
+    pub fn is_empty(&self) -> bool {
+        self.fds.is_empty()
+    }
+
+    /// Takes the socket at `index`, starting from `0`.
+    pub fn take(&mut self, index: usize) -> Option<OwnedFd> {
+        self.fds
+            .get_mut(index)
+            .and_then(Option::take)
+            .map(|(_, fd)| fd)
+    }
+
+    /// Takes the first socket named `name` in `LISTEN_FDNAMES`, such as the `FileDescriptorName`
+    /// of a systemd socket unit.
+    ///
+    /// Sockets without a name are named `unknown`.
+    pub fn take_named(&mut self, name: &str) -> Option<OwnedFd> {
+        self.fds
+            .iter_mut()
+            .find(|fd| matches!(fd, Some((fd_name, _)) if fd_name == name))
+            .and_then(Option::take)
+            .map(|(_, fd)| fd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        net::TcpListener,
+        os::unix::io::{AsRawFd, IntoRawFd, OwnedFd},
+    };
+
+    use super::{adopt, adopt_all, parse, ListenFds};
+
+    fn listener() -> OwnedFd {
+        TcpListener::bind(("127.0.0.1", 0)).unwrap().into()
+    }
+
+    #[test]
+    fn take() {
+        let mut fds = ListenFds {
+            fds: vec![
+                Some(("http".to_owned(), listener())),
+                Some(("unknown".to_owned(), listener())),
+                Some(("http".to_owned(), listener())),
+            ],
+        };
+        assert_eq!(fds.len(), 3);
+        assert!(fds.take_named("https").is_none());
+        assert!(fds.take_named("http").is_some());
+        assert!(fds.take_named("http").is_some());
+        assert!(fds.take_named("http").is_none());
+        assert!(fds.take(1).is_some());
+        assert!(fds.take(1).is_none());
+        assert!(fds.take(3).is_none());
+        assert_eq!(fds.len(), 3);
+    }
+
+    #[test]
+    fn parse_variables() {
+        assert_eq!(
+            parse(Some("7"), Some("3"), Some("http::https"), 7).unwrap(),
+            [
+                (3, "http".to_owned()),
+                (4, "unknown".to_owned()),
+                (5, "https".to_owned())
+            ]
+        );
+        assert_eq!(
+            parse(Some("7"), Some("1"), None, 7).unwrap(),
+            [(3, "unknown".to_owned())]
+        );
+        assert!(parse(Some("7"), Some("many"), None, 7).is_err());
+    }
+
+    #[test]
+    fn other_process() {
+        assert!(parse(Some("1"), Some("1"), None, 7).unwrap().is_empty());
+        assert!(parse(None, Some("1"), None, 7).unwrap().is_empty());
+    }
+
+    #[test]
+    fn adopt_sockets_only() {
+        let file = File::open("Cargo.toml").unwrap();
+        assert!(adopt(file.as_raw_fd()).is_err());
+        // The file descriptor was not taken, so it is still open.
+        assert!(file.metadata().is_ok());
+
+        let raw_fd = TcpListener::bind(("127.0.0.1", 0)).unwrap().into_raw_fd();
+        let fd = adopt(raw_fd).unwrap();
+        assert_eq!(fd.as_raw_fd(), raw_fd);
+    }
+
+    #[test]
+    fn skip_non_sockets() {
+        let file = File::open("Cargo.toml").unwrap();
+        let raw_fd = TcpListener::bind(("127.0.0.1", 0)).unwrap().into_raw_fd();
+        let mut fds = ListenFds {
+            fds: adopt_all(vec![
+                (file.as_raw_fd(), "file".to_owned()),
+                (raw_fd, "http".to_owned()),
+            ]),
+        };
+        assert_eq!(fds.len(), 2);
+        assert!(fds.take(0).is_none());
+        assert_eq!(fds.take(1).unwrap().as_raw_fd(), raw_fd);
+        assert!(file.metadata().is_ok());
+    }
+}
@@ -3,11 +3,17 @@ use std::{
     convert::TryFrom,
     fmt,
     future::{ready, Ready},
+    marker::PhantomData,
     path::PathBuf,
     str::FromStr,
+    sync::Arc,
 };
 
 use percent_encoding::percent_decode_str;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde_urlencoded::de;
 
 use crate::{
     errors::FilterError,
@@ -17,6 +23,7 @@ use crate::{
     outcome::{Outcome, RequestOutcome},
     request::{Request, RequestState},
     response::default_response,
+    router::{parse_pattern, split_path, Segment},
     uri::{uri, Uri},
     Filter, FilterBase, Forwarding, Responder, Response, Result, StatusCode,
 };
@@ -78,6 +85,73 @@ pub fn param<T: FromStr + Send>() -> impl_Filter!(T => Copy + (fmt::Debug)) {
         .recover_forward(|_: ForwardParam| ready(Ok(Forwarding::NotFound)))
 }
 
+/// Creates a [`Filter`] that extracts a segment like [`param()`], if the percent-decoded segment
+/// matches `regex` entirely, forwarding with [`Forwarding::NotFound`] otherwise.
+///
+/// The regex is compiled once, and shared by every request.
+///
+/// # Panics
+///
+/// Panics if the regex is invalid.
+///
+/// # Example
+///
+/// ```
+/// use myth::Filter;
+///
+/// let item = myth::path::literal("items")
+///     .and(myth::path::param_matching::<String>("[0-9a-f]{8}"))
+///     .handle(|id: String| async move { Ok(format!("Item {}", id)) });
+/// ```
+#[cfg(feature = "regex")]
+#[cfg_attr(myth_docs, doc(cfg(feature = "regex")))]
+pub fn param_matching<T>(regex: &str) -> impl_Filter!(T => Clone + (fmt::Debug))
+where
+    T: FromStr + Send + 'static,
+{
+    let regex = Regex::new(&format!("^(?:{})$", regex))
+        .unwrap_or_else(|error| panic!("invalid regex {:?}: {}", regex, error));
+    ready_filter(move |request, request_state| {
+        decoded_segment(request, request_state, |segment| {
+            if regex.is_match(&segment) {
+                Some((segment.parse().ok()?,))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+/// Creates a [`Filter`] that extracts a segment like [`param()`], if `predicate` returns `true`
+/// for it, forwarding with [`Forwarding::NotFound`] otherwise.
+///
+/// # Example
+///
+/// ```
+/// use myth::Filter;
+///
+/// let page = myth::path::literal("pages")
+///     .and(myth::path::param_where(|page: &u32| (1..=100).contains(page)))
+///     .handle(|page: u32| async move { Ok(format!("Page {}", page)) });
+/// ```
+pub fn param_where<T, P>(predicate: P) -> impl_Filter!(T => Clone + (fmt::Debug))
+where
+    T: FromStr + Send + 'static,
+    P: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    let predicate = Arc::new(predicate);
+    ready_filter(move |request, request_state| {
+        decoded_segment(request, request_state, |segment| {
+            let value = segment.parse().ok()?;
+            if predicate(&value) {
+                Some((value,))
+            } else {
+                None
+            }
+        })
+    })
+}
+
 pub fn literal(value: impl Into<String>) -> impl_Filter!(() => Clone + (fmt::Debug)) {
     let value = value.into();
     assert!(!value.is_empty(), "literal segments cannot be empty");
@@ -142,12 +216,14 @@ pub fn end() -> impl_Filter!(() => Copy + (fmt::Debug)) {
             let current_path = request_state.current_path(request);
             match current_path {
                 "" => Outcome::Success(()),
-                "/" => Outcome::Error(
-                    Redirect {
-                        location: request_state.previous_path(request).to_owned(),
-                    }
-                    .into(),
-                ),
+                "/" => {
+                    let path = request_state.previous_path(request);
+                    let location = match &request_state.origin {
+                        Some(origin) => format!("{}{}", origin, path),
+                        None => path.to_owned(),
+                    };
+                    Outcome::Error(Redirect { location }.into())
+                }
                 _ => Outcome::Forward {
                     input: (),
                     forwarding: Forwarding::NotFound,
@@ -163,6 +239,10 @@ pub struct Redirect {
 }
 
 impl Redirect {
+    pub(crate) fn new(location: String) -> Self {
+        Self { location }
+    }
+
     pub fn location(&self) -> &str {
         &self.location
     }
@@ -282,11 +362,312 @@ pub fn tail_path() -> impl_Filter!(PathBuf => Copy + (fmt::Debug)) {
     })
 }
 
+/// Creates a [`Filter`] that matches the rest of the path against a template, and
+/// deserializes its captures into `T`.
+///
+/// Templates use the syntax of [`Router`](crate::Router) patterns, such as
+/// `/orgs/{org}/repos/{repo}` or `/files/{*path}`, and are parsed once. Neither parameters nor
+/// catch-alls capture `.` or `..` segments, even percent-encoded ones. Captures are
+/// percent-decoded, and deserialized like [`query::deserialize()`](crate::query::deserialize)
+/// deserializes query strings, so fields can be strings, numbers, or other values that parse
+/// from strings. If the path does not match the template, the [`Filter`] forwards with
+/// [`Forwarding::NotFound`], unless it only has an extra trailing slash, which errors with a
+/// [`Redirect`] to the path without it, like [`end()`]. By default, it also forwards if the
+/// captures cannot be deserialized, and with [`Template::reject_invalid()`] it errors with a
+/// [`TemplateError`] instead.
+///
+/// # Panics
+///
+/// Panics if the template is invalid.
+///
+/// # Example
+///
+/// ```
+/// use myth::Filter;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Repo {
+///     org: String,
+///     repo: String,
+/// }
+///
+/// let repo = myth::path::template::<Repo>("/orgs/{org}/repos/{repo}")
+///     .handle(|repo: Repo| async move { Ok(format!("{}/{}", repo.org, repo.repo)) });
+/// ```
+pub fn template<T>(template: &str) -> Template<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    Template {
+        segments: parse_pattern(template).into(),
+        reject_invalid: false,
+        marker: PhantomData,
+    }
+}
+
+/// A [`Filter`] that matches a path template, created by [`template()`].
+pub struct Template<T> {
+    segments: Arc<[Segment]>,
+    reject_invalid: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Template<T> {
+    /// Returns the parsed segments of the template, for naming it in [`Routes`](crate::url::Routes).
+    pub(crate) fn segments(&self) -> Arc<[Segment]> {
+        Arc::clone(&self.segments)
+    }
+
+    /// Errors with a [`TemplateError`], which responds with `400 Bad Request`, instead of
+    /// forwarding when the path matches but its captures cannot be deserialized.
+    pub fn reject_invalid(mut self) -> Self {
+        self.reject_invalid = true;
+        self
+    }
+
+    /// Matches `path`, returning the names and percent-decoded values of its captures.
+    fn captures(&self, path: &str) -> Option<Vec<(Arc<str>, String)>> {
+        let parts = split_path(path);
+        let mut captures = Vec::new();
+        for (index, segment) in self.segments.iter().enumerate() {
+            let (start, part) = parts.get(index)?;
+            match segment {
+                Segment::Literal(literal) if literal == part => {}
+                Segment::Param(name) if !part.is_empty() && part != "." && part != ".." => {
+                    captures.push((Arc::clone(name), part.clone().into_owned()));
+                }
+                Segment::CatchAll(name) if !path[*start..].is_empty() => {
+                    let value = percent_decode_str(&path[*start..]).decode_utf8_lossy();
+                    // Like the catch-alls of `Router`s, which are often joined to a directory.
+                    if value
+                        .split('/')
+                        .any(|segment| segment == "." || segment == "..")
+                    {
+                        return None;
+                    }
+                    captures.push((Arc::clone(name), value.into_owned()));
+                    return Some(captures);
+                }
+                _ => return None,
+            }
+        }
+        if parts.len() == self.segments.len() {
+            Some(captures)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Clone for Template<T> {
+    fn clone(&self) -> Self {
+        Self {
+            segments: Arc::clone(&self.segments),
+            reject_invalid: self.reject_invalid,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Template<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Template")
+            .field("segments", &self.segments)
+            .field("reject_invalid", &self.reject_invalid)
+            .finish()
+    }
+}
+
+impl<T> FilterSealed for Template<T> {}
+
+impl<'f, T> FilterBase<'f> for Template<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    type Input = ();
+
+    type Success = (T,);
+}
+
+impl<'f, T> FilterExecute<'f> for Template<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    type Future = Ready<RequestOutcome<Self::Input, Self::Success>>;
+
+    fn execute(
+        &'f self,
+        request: &'f Request,
+        mut request_state: RequestState,
+        (): Self::Input,
+    ) -> Self::Future {
+        let forward = Outcome::Forward {
+            input: (),
+            forwarding: Forwarding::NotFound,
+        };
+        let path = request_state.current_path(request);
+        let outcome = match self.captures(path) {
+            Some(captures) => {
+                let pairs: Vec<(&str, &str)> = captures
+                    .iter()
+                    .map(|(name, value)| (name.as_ref(), value.as_str()))
+                    .collect();
+                // `serde_urlencoded` only deserializes encoded strings, and it is what parses
+                // numbers and other values from the captured strings, so they are encoded first.
+                let encoded =
+                    serde_urlencoded::to_string(pairs).expect("failed to encode captures");
+                match serde_urlencoded::from_str(&encoded) {
+                    Ok(value) => {
+                        request_state.end_current_path_index(request);
+                        Outcome::Success((value,))
+                    }
+                    Err(error) if self.reject_invalid => {
+                        Outcome::Error(TemplateError(error).into())
+                    }
+                    Err(error) => {
+                        tracing::debug!("forwarding for template deserialization error: {}", error);
+                        forward
+                    }
+                }
+            }
+            // Like `end()` and `Router`s, redirect to the path without a trailing slash.
+            None => match path.strip_suffix('/') {
+                Some(trimmed) if !trimmed.is_empty() && self.captures(trimmed).is_some() => {
+                    let location = format!(
+                        "{}{}{}",
+                        request_state.origin.as_deref().unwrap_or(""),
+                        request_state.previous_path(request),
+                        trimmed,
+                    );
+                    Outcome::Error(Redirect::new(location).into())
+                }
+                _ => forward,
+            },
+        };
+        ready(RequestOutcome {
+            request_state,
+            outcome,
+        })
+    }
+}
+
+/// An error deserializing the captures of a [`template()`], which responds with
+/// `400 Bad Request`.
+#[derive(Debug)]
+pub struct TemplateError(de::Error);
+
+impl TemplateError {
+    /// Returns the underlying deserialization error.
+    pub fn into_inner(self) -> de::Error {
+        self.0
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to deserialize path: {}", self.0)
+    }
+}
+
+impl FilterError for TemplateError {
+    fn into_response(self: Box<Self>) -> Response {
+        tracing::debug!(
+            "default response for template deserialization error: {}",
+            self
+        );
+        default_response(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Creates a [`Filter`] that matches a path made of literal segments and typed parameters.
+///
+/// Segments are separated by `/`. A string literal matches a segment exactly, like
+/// [`literal()`], and a type extracts a segment, like [`param()`]. The [`Filter`] succeeds with
+/// the parameters in order, and the rest of the path must be empty, like [`end()`], unless the
+/// last segment is `..`. Types with more than one token, such as `std::net::IpAddr`, must be
+/// wrapped in parentheses.
+///
+/// # Example
+///
+/// ```
+/// use myth::Filter;
+///
+/// // Matches `/users/5/posts/12`, succeeding with `(5, 12)`.
+/// let post = myth::path!("users" / u64 / "posts" / u64)
+///     .handle(|user: u64, post: u64| async move { Ok(format!("Post {} by {}", post, user)) });
+///
+/// // Matches `/static/` and anything below it.
+/// let files = myth::path!("static" / ..).and(myth::path::tail_path());
+/// ```
+///
+/// Invalid literal segments are rejected at compile time:
+///
+/// ```compile_fail
+/// let filter = myth::path!("users/posts" / u64);
+/// ```
+#[macro_export]
+macro_rules! path {
+    () => {
+        $crate::path::end()
+    };
+    ($($segments:tt)+) => {
+        $crate::__path!(@start $($segments)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __path {
+    (@start ..) => {
+        $crate::any()
+    };
+    (@start $segment:tt $($rest:tt)*) => {
+        $crate::__path!(@and $crate::__path!(@segment $segment); $($rest)*)
+    };
+    (@and $filter:expr;) => {
+        $crate::Filter::and($filter, $crate::path::end())
+    };
+    (@and $filter:expr; / ..) => {
+        $filter
+    };
+    (@and $filter:expr; / $segment:tt $($rest:tt)*) => {
+        $crate::__path!(@and $crate::Filter::and($filter, $crate::__path!(@segment $segment)); $($rest)*)
+    };
+    (@segment ..) => {
+        ::core::compile_error!("`..` can only be the last segment of `path!`")
+    };
+    (@segment $literal:literal) => {{
+        const _: () = $crate::path::__check_literal($literal);
+        $crate::path::literal($literal)
+    }};
+    (@segment $param:ty) => {
+        $crate::path::param::<$param>()
+    };
+}
+
+/// Checks a literal segment of [`path!`] at compile time, with the same rules as [`literal()`].
+#[doc(hidden)]
+pub const fn __check_literal(segment: &str) {
+    let bytes = segment.as_bytes();
+    assert!(!bytes.is_empty(), "literal segments cannot be empty");
+    let mut i = 0;
+    while i < bytes.len() {
+        assert!(bytes[i] != b'/', "literal segments cannot contain a slash");
+        i += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{borrow::Cow, path::PathBuf};
 
-    use super::{end, literal, param, param_str, sanitize_path, Redirect};
+    use serde::Deserialize;
+
+    use super::{
+        end, literal, param, param_str, param_where, sanitize_path, tail, template, Redirect,
+        TemplateError,
+    };
     use crate::{test, uri::Uri, Filter};
 
     #[test]
@@ -407,4 +788,137 @@ mod tests {
         let redirect: Redirect = test::get().uri("/hhhhhhh/").error(&filter).await;
         assert_eq!(redirect.location(), "/hhhhhhh");
     }
+
+    #[tokio::test]
+    async fn path_macro() {
+        let filter = crate::path!("users" / u64 / "posts" / (std::primitive::u32));
+        test::get()
+            .uri("/users/5/posts/12")
+            .success(&filter, |user: u64, post: u32| {
+                assert_eq!((user, post), (5, 12));
+            })
+            .await;
+        test::get().uri("/users/5/posts").not_found(&filter).await;
+        test::get()
+            .uri("/users/5/posts/12/more")
+            .not_found(&filter)
+            .await;
+        test::get()
+            .uri("/users/five/posts/12")
+            .not_found(&filter)
+            .await;
+
+        let filter = crate::path!("static" / ..).and(tail());
+        test::get()
+            .uri("/static/css/main.css")
+            .success(&filter, |tail: &str| assert_eq!(tail, "/css/main.css"))
+            .await;
+
+        test::get().uri("/").succeeds(&crate::path!()).await;
+        test::get()
+            .uri("/anything")
+            .succeeds(&crate::path!(..))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn path_template() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Repo {
+            org: String,
+            repo: u32,
+        }
+
+        let filter = template::<Repo>("/orgs/{org}/repos/{repo}");
+        test::get()
+            .uri("/orgs/my%20org/repos/5")
+            .success(&filter, |repo: Repo| {
+                assert_eq!(
+                    repo,
+                    Repo {
+                        org: "my org".to_owned(),
+                        repo: 5
+                    }
+                )
+            })
+            .await;
+        for uri in [
+            "/orgs/a/repos",
+            "/orgs/a/repos/5/more",
+            "/orgs//repos/5",
+            "/orgs/a/repos/five",
+        ] {
+            test::get().uri(uri).not_found(&filter).await;
+        }
+
+        let redirect: Redirect = test::get().uri("/orgs/a/repos/5/").error(&filter).await;
+        assert_eq!(redirect.location(), "/orgs/a/repos/5");
+        let redirect: Redirect = test::get()
+            .uri("/api/orgs/a/repos/5/")
+            .error(&crate::path!("api" / ..).and(filter.clone()))
+            .await;
+        assert_eq!(redirect.location(), "/api/orgs/a/repos/5");
+
+        let error: TemplateError = test::get()
+            .uri("/orgs/a/repos/five")
+            .error(&filter.reject_invalid())
+            .await;
+        assert!(error.to_string().starts_with("failed to deserialize path"));
+
+        #[derive(Deserialize)]
+        struct File {
+            path: String,
+        }
+
+        let filter = crate::path!("api" / ..).and(template::<File>("/files/{*path}"));
+        test::get()
+            .uri("/api/files/css/main%201.css")
+            .success(&filter, |file: File| {
+                assert_eq!(file.path, "css/main 1.css")
+            })
+            .await;
+        test::get().uri("/api/files/").not_found(&filter).await;
+        test::get()
+            .uri("/api/files/a/%2E%2E/secret")
+            .not_found(&filter)
+            .await;
+
+        #[derive(Deserialize)]
+        struct Name {
+            name: String,
+        }
+
+        let filter = template::<Name>("/files/{name}");
+        for uri in ["/files/..", "/files/%2E%2E", "/files/."] {
+            test::get().uri(uri).not_found(&filter).await;
+        }
+        test::get()
+            .uri("/files/..a")
+            .success(&filter, |name: Name| assert_eq!(name.name, "..a"))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn constrained_params() {
+        let filter = param_where(|page: &u32| (1..=100).contains(page))
+            .handle(|page: u32| async move { Ok(format!("page {}", page)) })
+            .or(crate::any().handle(|| async { Ok("other".to_owned()) }));
+        for (uri, body) in [("/5", "page 5"), ("/500", "other"), ("/five", "other")] {
+            let response = test::get().uri(uri).response(&filter).await;
+            assert_eq!(response.body(), body, "{}", uri);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[tokio::test]
+    async fn regex_params() {
+        let filter = crate::path!("items" / ..).and(super::param_matching::<String>("[0-9a-f]{8}"));
+        test::get()
+            .uri("/items/0123abcd")
+            .success(&filter, |id: String| assert_eq!(id, "0123abcd"))
+            .await;
+        for uri in ["/items/0123abc", "/items/0123abcde", "/items/0123ABCD"] {
+            test::get().uri(uri).not_found(&filter).await;
+        }
+    }
 }
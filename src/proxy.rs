@@ -0,0 +1,841 @@
+//! The [PROXY protocol](https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt), used by
+//! load balancers to pass on the address of the client.
+//!
+//! [`Server::proxy_protocol()`](crate::Server::proxy_protocol) reads a version 1 or version 2
+//! header from the start of every connection, so that [`remote_addr()`](crate::remote_addr)
+//! returns the address of the client instead of the address of the load balancer. The full
+//! header, including any TLV fields, is available through the [`header()`] filter.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use myth::{proxy::ProxyConfig, Filter, PeerAddr};
+//!
+//! # #[tokio::main] async fn main() {
+//! let filter = myth::remote_addr()
+//!     .handle(|addr: PeerAddr| async move { Ok(format!("Hello, {}!", addr)) });
+//!
+//! // Only trust headers sent by our load balancers.
+//! let config = ProxyConfig::new().trusted(["10.0.0.0/8".parse().unwrap()]);
+//!
+//! myth::serve(filter)
+//!     .bind(([0, 0, 0, 0], 8080))
+//!     .proxy_protocol(config)
+//!     .run()
+//!     .await;
+//! # }
+//! ```
+
+use std::{
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::PathBuf,
+    pin::Pin,
+    str,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{stream::FuturesUnordered, Stream};
+use hyper::server::accept::Accept;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+use crate::{
+    filter::ready::ready_filter,
+    impl_Filter,
+    outcome::Outcome,
+    service::{Incoming, RequestStream},
+    Bytes, IpNet, PeerAddr,
+};
+
+/// The signature at the start of a version 2 header.
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The prefix of a version 1 header.
+const V1_PREFIX: &[u8; 6] = b"PROXY ";
+
+/// The maximum length of a version 1 header, including the CRLF.
+const V1_MAX_LEN: usize = 107;
+
+/// The length of the fixed part of a version 2 header.
+const V2_FIXED_LEN: usize = 16;
+
+/// A configuration for reading PROXY protocol headers, to be used with
+/// [`Server::proxy_protocol()`](crate::Server::proxy_protocol).
+#[derive(Clone, Debug)]
+pub struct ProxyConfig {
+    trusted: Vec<IpNet>,
+    required: bool,
+    timeout: Duration,
+    max_pending: usize,
+}
+
+impl ProxyConfig {
+    /// Creates a new configuration.
+    ///
+    /// By default, every connection must start with a header, headers are trusted from any
+    /// source, the header must be received within 10 seconds, and headers are read from at most
+    /// 1024 connections at once.
+    pub fn new() -> Self {
+        Self {
+            trusted: Vec::new(),
+            required: true,
+            timeout: Duration::from_secs(10),
+            max_pending: 1024,
+        }
+    }
+
+    /// Only reads headers from connections whose address is within one of `sources`.
+    ///
+    /// Connections from other sources are used as-is, so a header sent by them is treated as
+    /// part of the request.
+    pub fn trusted(mut self, sources: impl IntoIterator<Item = IpNet>) -> Self {
+        self.trusted.extend(sources);
+        self
+    }
+
+    /// Sets whether trusted connections must start with a header. Connections without one are
+    /// closed.
+    ///
+    /// Otherwise, connections without a header keep their own address.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets how long to wait for the header before closing the connection.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum number of connections whose header is read at once. While this many are
+    /// pending, no more connections are accepted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn max_pending(mut self, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "the maximum number of pending headers must not be zero"
+        );
+        self.max_pending = max;
+        self
+    }
+
+    fn is_trusted(&self, addr: &PeerAddr) -> bool {
+        if self.trusted.is_empty() {
+            return true;
+        }
+        match addr.ip() {
+            Some(ip) => self.trusted.iter().any(|net| net.contains(&ip)),
+            None => false,
+        }
+    }
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A PROXY protocol header, which describes the connection that a load balancer received.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyHeader {
+    version: u8,
+    source: Option<PeerAddr>,
+    destination: Option<PeerAddr>,
+    tlvs: Vec<Tlv>,
+}
+
+impl ProxyHeader {
+    /// Returns the version of the PROXY protocol, either `1` or `2`.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the address of the client.
+    ///
+    /// This is [`None`] if the load balancer did not know it, such as for health checks.
+    pub fn source(&self) -> Option<&PeerAddr> {
+        self.source.as_ref()
+    }
+
+    /// Returns the address that the client connected to.
+    pub fn destination(&self) -> Option<&PeerAddr> {
+        self.destination.as_ref()
+    }
+
+    /// Returns the TLV fields of a version 2 header.
+    pub fn tlvs(&self) -> &[Tlv] {
+        &self.tlvs
+    }
+
+    /// Returns the value of the first TLV field of type `kind`.
+    pub fn tlv(&self, kind: u8) -> Option<&[u8]> {
+        self.tlvs
+            .iter()
+            .find(|tlv| tlv.kind == kind)
+            .map(|tlv| &*tlv.value)
+    }
+
+    /// Returns the ALPN protocol that the client negotiated, from [`Tlv::ALPN`].
+    pub fn alpn(&self) -> Option<&[u8]> {
+        self.tlv(Tlv::ALPN)
+    }
+
+    /// Returns the host name that the client requested, from [`Tlv::AUTHORITY`].
+    pub fn authority(&self) -> Option<&str> {
+        self.tlv(Tlv::AUTHORITY)
+            .and_then(|value| str::from_utf8(value).ok())
+    }
+
+    /// Returns the unique ID of the connection, from [`Tlv::UNIQUE_ID`].
+    pub fn unique_id(&self) -> Option<&[u8]> {
+        self.tlv(Tlv::UNIQUE_ID)
+    }
+}
+
+/// A type-length-value field of a version 2 [`ProxyHeader`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tlv {
+    kind: u8,
+    value: Bytes,
+}
+
+impl Tlv {
+    /// The ALPN protocol negotiated by the client.
+    pub const ALPN: u8 = 0x01;
+    /// The host name requested by the client.
+    pub const AUTHORITY: u8 = 0x02;
+    /// A CRC32c checksum of the header.
+    pub const CRC32C: u8 = 0x03;
+    /// Padding, which should be ignored.
+    pub const NOOP: u8 = 0x04;
+    /// A unique ID of the connection.
+    pub const UNIQUE_ID: u8 = 0x05;
+    /// Information about the TLS connection of the client.
+    pub const SSL: u8 = 0x20;
+    /// The network namespace of the connection.
+    pub const NETNS: u8 = 0x30;
+
+    /// Returns the type of this field.
+    pub fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    /// Returns the value of this field.
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts the [`ProxyHeader`] of the connection, or
+/// [`None`] if the connection did not send one.
+pub fn header() -> impl_Filter!(Option<Arc<ProxyHeader>> => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| Outcome::Success((request.connection.proxy_header.clone(),)))
+}
+
+/// The result of parsing the start of a connection.
+#[derive(Debug, PartialEq)]
+enum Parsed {
+    /// More bytes are needed.
+    Incomplete,
+
+    /// The connection does not start with a header.
+    Missing,
+
+    /// A header of `len` bytes.
+    Complete { header: ProxyHeader, len: usize },
+}
+
+/// An invalid PROXY protocol header.
+#[derive(Debug)]
+struct InvalidHeader(&'static str);
+
+impl fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid PROXY protocol header: {}", self.0)
+    }
+}
+
+fn parse(buf: &[u8]) -> Result<Parsed, InvalidHeader> {
+    if starts_with_partial(buf, V2_SIGNATURE) {
+        if buf.len() < V2_SIGNATURE.len() {
+            return Ok(Parsed::Incomplete);
+        }
+        parse_v2(buf)
+    } else if starts_with_partial(buf, V1_PREFIX) {
+        if buf.len() < V1_PREFIX.len() {
+            return Ok(Parsed::Incomplete);
+        }
+        parse_v1(buf)
+    } else {
+        Ok(Parsed::Missing)
+    }
+}
+
+/// Returns whether `buf` and `prefix` start with the same bytes, up to the shorter length.
+fn starts_with_partial(buf: &[u8], prefix: &[u8]) -> bool {
+    let len = buf.len().min(prefix.len());
+    buf[..len] == prefix[..len]
+}
+
+fn parse_v1(buf: &[u8]) -> Result<Parsed, InvalidHeader> {
+    let end = match buf.iter().take(V1_MAX_LEN).position(|&byte| byte == b'\n') {
+        Some(end) => end,
+        None if buf.len() >= V1_MAX_LEN => return Err(InvalidHeader("line too long")),
+        None => return Ok(Parsed::Incomplete),
+    };
+    let line = buf[..end]
+        .strip_suffix(b"\r")
+        .ok_or(InvalidHeader("missing CR"))?;
+    let line = str::from_utf8(line).map_err(|_| InvalidHeader("invalid UTF-8"))?;
+
+    let mut parts = line.split(' ').skip(1);
+    let protocol = parts.next().ok_or(InvalidHeader("missing protocol"))?;
+    let (source, destination) = match protocol {
+        "UNKNOWN" => (None, None),
+        "TCP4" | "TCP6" => {
+            let mut next = || parts.next().ok_or(InvalidHeader("missing address"));
+            let (source_ip, destination_ip) = (next()?, next()?);
+            let (source_port, destination_port) = (next()?, next()?);
+            if parts.next().is_some() {
+                return Err(InvalidHeader("too many fields"));
+            }
+            let ip = |ip: &str| -> Result<IpAddr, InvalidHeader> {
+                if protocol == "TCP4" {
+                    ip.parse::<Ipv4Addr>().map(IpAddr::from)
+                } else {
+                    ip.parse::<Ipv6Addr>().map(IpAddr::from)
+                }
+                .map_err(|_| InvalidHeader("invalid address"))
+            };
+            let port = |port: &str| port.parse().map_err(|_| InvalidHeader("invalid port"));
+            (
+                Some(PeerAddr::from((ip(source_ip)?, port(source_port)?))),
+                Some(PeerAddr::from((
+                    ip(destination_ip)?,
+                    port(destination_port)?,
+                ))),
+            )
+        }
+        _ => return Err(InvalidHeader("unknown protocol")),
+    };
+
+    Ok(Parsed::Complete {
+        header: ProxyHeader {
+            version: 1,
+            source,
+            destination,
+            tlvs: Vec::new(),
+        },
+        len: end + 1,
+    })
+}
+
+fn parse_v2(buf: &[u8]) -> Result<Parsed, InvalidHeader> {
+    if buf.len() < V2_FIXED_LEN {
+        return Ok(Parsed::Incomplete);
+    }
+    let version_command = buf[12];
+    if version_command >> 4 != 2 {
+        return Err(InvalidHeader("unsupported version"));
+    }
+    let family = buf[13];
+    let len = V2_FIXED_LEN + usize::from(u16::from_be_bytes([buf[14], buf[15]]));
+    if buf.len() < len {
+        return Ok(Parsed::Incomplete);
+    }
+    let body = &buf[V2_FIXED_LEN..len];
+
+    let addr_len = match family >> 4 {
+        0x0 => 0,
+        0x1 => 12,
+        0x2 => 36,
+        0x3 => 216,
+        _ => return Err(InvalidHeader("unknown address family")),
+    };
+    if body.len() < addr_len {
+        return Err(InvalidHeader("address block too short"));
+    }
+    let (addrs, mut tlv_buf) = body.split_at(addr_len);
+
+    let (source, destination) = match version_command & 0x0f {
+        // LOCAL: the connection was made by the load balancer itself.
+        0x0 => (None, None),
+        // PROXY: only connections over a stream transport are supported.
+        0x1 if family & 0x0f != 0x1 => return Err(InvalidHeader("unsupported transport")),
+        0x1 => match family >> 4 {
+            0x1 => {
+                let ip = |bytes: &[u8]| IpAddr::from(<[u8; 4]>::try_from(bytes).unwrap());
+                let port = u16::from_be_bytes([addrs[8], addrs[9]]);
+                let destination_port = u16::from_be_bytes([addrs[10], addrs[11]]);
+                (
+                    Some(PeerAddr::from((ip(&addrs[0..4]), port))),
+                    Some(PeerAddr::from((ip(&addrs[4..8]), destination_port))),
+                )
+            }
+            0x2 => {
+                let ip = |bytes: &[u8]| IpAddr::from(<[u8; 16]>::try_from(bytes).unwrap());
+                let port = u16::from_be_bytes([addrs[32], addrs[33]]);
+                let destination_port = u16::from_be_bytes([addrs[34], addrs[35]]);
+                (
+                    Some(PeerAddr::from((ip(&addrs[0..16]), port))),
+                    Some(PeerAddr::from((ip(&addrs[16..32]), destination_port))),
+                )
+            }
+            0x3 => (
+                Some(unix_addr(&addrs[0..108])),
+                Some(unix_addr(&addrs[108..216])),
+            ),
+            _ => (None, None),
+        },
+        _ => return Err(InvalidHeader("unknown command")),
+    };
+
+    let mut tlvs = Vec::new();
+    while !tlv_buf.is_empty() {
+        if tlv_buf.len() < 3 {
+            return Err(InvalidHeader("truncated TLV"));
+        }
+        let value_len = usize::from(u16::from_be_bytes([tlv_buf[1], tlv_buf[2]]));
+        let value = tlv_buf
+            .get(3..3 + value_len)
+            .ok_or(InvalidHeader("truncated TLV"))?;
+        tlvs.push(Tlv {
+            kind: tlv_buf[0],
+            value: Bytes::copy_from_slice(value),
+        });
+        tlv_buf = &tlv_buf[3 + value_len..];
+    }
+
+    Ok(Parsed::Complete {
+        header: ProxyHeader {
+            version: 2,
+            source,
+            destination,
+            tlvs,
+        },
+        len,
+    })
+}
+
+fn unix_addr(bytes: &[u8]) -> PeerAddr {
+    let len = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    if len == 0 {
+        return PeerAddr::Unix(None);
+    }
+    let path = String::from_utf8_lossy(&bytes[..len]).into_owned();
+    PeerAddr::Unix(Some(PathBuf::from(path)))
+}
+
+/// Reads the header from the start of `stream`, returning [`None`] if the connection should be
+/// closed.
+async fn read_header<S>(mut stream: S, config: Arc<ProxyConfig>) -> Option<ProxyStream<S>>
+where
+    S: RequestStream,
+{
+    let remote_addr = stream.remote_addr();
+    if !config.is_trusted(&remote_addr) {
+        return Some(ProxyStream {
+            inner: stream,
+            buffered: Bytes::new(),
+            header: None,
+            remote_addr,
+        });
+    }
+
+    let read = async {
+        let mut buf = Vec::with_capacity(V1_MAX_LEN);
+        loop {
+            match parse(&buf) {
+                Ok(Parsed::Incomplete) => {}
+                Ok(Parsed::Missing) => return Ok((None, buf)),
+                Ok(Parsed::Complete { header, len }) => {
+                    buf.drain(..len);
+                    return Ok((Some(header), buf));
+                }
+                Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error.0)),
+            }
+            if stream.read_buf(&mut buf).await? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    };
+    let (header, buffered) = match tokio::time::timeout(config.timeout, read).await {
+        Ok(Ok(read)) => read,
+        Ok(Err(error)) => {
+            tracing::debug!(
+                "Failed to read PROXY header from {}: {}",
+                remote_addr,
+                error
+            );
+            return None;
+        }
+        Err(_) => {
+            tracing::debug!("Timed out reading PROXY header from {}", remote_addr);
+            return None;
+        }
+    };
+    if header.is_none() && config.required {
+        tracing::debug!("Missing PROXY header from {}", remote_addr);
+        return None;
+    }
+
+    let header = header.map(Arc::new);
+    let remote_addr = header
+        .as_ref()
+        .and_then(|header| header.source.clone())
+        .unwrap_or(remote_addr);
+    Some(ProxyStream {
+        inner: stream,
+        buffered: buffered.into(),
+        header,
+        remote_addr,
+    })
+}
+
+type ReadHeader<S> = Pin<Box<dyn Future<Output = Option<ProxyStream<S>>> + Send>>;
+
+pin_project! {
+    /// An [`Incoming`] that reads a PROXY protocol header from each connection, created by
+    /// [`Server::proxy_protocol()`](crate::Server::proxy_protocol).
+    ///
+    /// Headers are read from many connections at once, up to [`ProxyConfig::max_pending()`], and
+    /// a connection is only accepted once its header has been read.
+    pub struct ProxyAcceptor<I: Accept> {
+        #[pin]
+        incoming: I,
+        config: Arc<ProxyConfig>,
+        pending: FuturesUnordered<ReadHeader<I::Conn>>,
+        incoming_done: bool,
+    }
+}
+
+impl<I> ProxyAcceptor<I>
+where
+    I: Accept,
+{
+    pub(crate) fn new(incoming: I, config: ProxyConfig) -> Self {
+        Self {
+            incoming,
+            config: Arc::new(config),
+            pending: FuturesUnordered::new(),
+            incoming_done: false,
+        }
+    }
+}
+
+impl<I> fmt::Debug for ProxyAcceptor<I>
+where
+    I: Accept + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyAcceptor")
+            .field("incoming", &self.incoming)
+            .field("config", &self.config)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl<I> Incoming for ProxyAcceptor<I>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+{
+    type Addr = I::Addr;
+
+    fn local_addr(&self) -> Self::Addr {
+        self.incoming.local_addr()
+    }
+
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        self.incoming.set_tcp_options(nodelay, keepalive);
+    }
+}
+
+impl<I> Accept for ProxyAcceptor<I>
+where
+    I: Accept,
+    I::Conn: RequestStream,
+{
+    type Conn = ProxyStream<I::Conn>;
+
+    type Error = I::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            // Once `max_pending` headers are pending, the incoming connections wait until one of
+            // them has been read, which wakes this task.
+            while !*this.incoming_done && this.pending.len() < this.config.max_pending {
+                match this.incoming.as_mut().poll_accept(cx) {
+                    Poll::Ready(Some(Ok(stream))) => {
+                        let config = Arc::clone(this.config);
+                        this.pending.push(Box::pin(read_header(stream, config)));
+                    }
+                    Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                    Poll::Ready(None) => *this.incoming_done = true,
+                    Poll::Pending => break,
+                }
+            }
+
+            match Pin::new(&mut *this.pending).poll_next(cx) {
+                Poll::Ready(Some(Some(stream))) => return Poll::Ready(Some(Ok(stream))),
+                Poll::Ready(Some(None)) => {}
+                Poll::Ready(None) if *this.incoming_done => return Poll::Ready(None),
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A connection accepted by a [`ProxyAcceptor`], whose header has been read.
+#[derive(Debug)]
+pub struct ProxyStream<S> {
+    inner: S,
+    buffered: Bytes,
+    header: Option<Arc<ProxyHeader>>,
+    remote_addr: PeerAddr,
+}
+
+impl<S> ProxyStream<S> {
+    /// Returns the header that was read from the connection.
+    pub fn header(&self) -> Option<&ProxyHeader> {
+        self.header.as_deref()
+    }
+}
+
+impl<S> RequestStream for ProxyStream<S>
+where
+    S: RequestStream,
+{
+    fn remote_addr(&self) -> PeerAddr {
+        self.remote_addr.clone()
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        self.header
+            .as_ref()
+            .and_then(|header| header.destination.clone())
+            .or_else(|| self.inner.local_addr())
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.header.clone()
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls_info(&self) -> Option<Arc<crate::tls::TlsInfo>> {
+        self.inner.tls_info()
+    }
+}
+
+impl<S> AsyncRead for ProxyStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.buffered.is_empty() {
+            let len = this.buffered.len().min(buf.remaining());
+            buf.put_slice(&this.buffered.split_to(len));
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for ProxyStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{header, parse, Parsed, ProxyHeader, Tlv, V2_SIGNATURE};
+    use crate::{test, Bytes, PeerAddr};
+
+    fn complete(buf: &[u8]) -> (ProxyHeader, usize) {
+        match parse(buf).unwrap() {
+            Parsed::Complete { header, len } => (header, len),
+            parsed => panic!("expected a complete header, instead got {:?}", parsed),
+        }
+    }
+
+    #[test]
+    fn v1() {
+        let buf = b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nGET / HTTP/1.1\r\n";
+        let (header, len) = complete(buf);
+        assert_eq!(len, 45);
+        assert_eq!(header.version(), 1);
+        assert_eq!(
+            header.source(),
+            Some(&PeerAddr::from(([192, 0, 2, 1], 56324)))
+        );
+        assert_eq!(
+            header.destination(),
+            Some(&PeerAddr::from(([198, 51, 100, 1], 443)))
+        );
+
+        let (header, _) = complete(b"PROXY TCP6 2001:db8::1 2001:db8::2 1 2\r\n");
+        assert_eq!(header.source().unwrap().to_string(), "[2001:db8::1]:1");
+
+        let (header, _) = complete(b"PROXY UNKNOWN\r\n");
+        assert_eq!(header.source(), None);
+    }
+
+    #[test]
+    fn v1_invalid() {
+        assert!(parse(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 2001:db8::1 198.51.100.1 1 2\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.0.2.1 198.51.100.1 1 2\n").is_err());
+        assert!(parse(b"PROXY UDP4 192.0.2.1 198.51.100.1 1 2\r\n").is_err());
+        let mut long = b"PROXY TCP4 ".to_vec();
+        long.resize(200, b'1');
+        assert!(parse(&long).is_err());
+    }
+
+    #[test]
+    fn incomplete_or_missing() {
+        assert_eq!(parse(b"").unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(b"PRO").unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(b"PROXY TCP4 192.0.2.1").unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(&V2_SIGNATURE[..5]).unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(b"GET / HTTP/1.1\r\n").unwrap(), Parsed::Missing);
+        assert_eq!(parse(b"\r\n\r\nX").unwrap(), Parsed::Missing);
+    }
+
+    #[test]
+    fn v2() {
+        let mut buf = V2_SIGNATURE.to_vec();
+        // PROXY command over TCP4, with 12 bytes of addresses and a 7 byte TLV.
+        buf.extend_from_slice(&[0x21, 0x11, 0, 19]);
+        buf.extend_from_slice(&[192, 0, 2, 1, 198, 51, 100, 1, 0xdc, 0x04, 0x01, 0xbb]);
+        buf.extend_from_slice(&[Tlv::AUTHORITY, 0, 4]);
+        buf.extend_from_slice(b"myth");
+        assert_eq!(parse(&buf[..20]).unwrap(), Parsed::Incomplete);
+        buf.extend_from_slice(b"GET");
+
+        let (header, len) = complete(&buf);
+        assert_eq!(len, buf.len() - 3);
+        assert_eq!(header.version(), 2);
+        assert_eq!(
+            header.source(),
+            Some(&PeerAddr::from(([192, 0, 2, 1], 56324)))
+        );
+        assert_eq!(
+            header.destination(),
+            Some(&PeerAddr::from(([198, 51, 100, 1], 443)))
+        );
+        assert_eq!(header.authority(), Some("myth"));
+        assert_eq!(header.tlvs().len(), 1);
+        assert_eq!(header.tlvs()[0].kind(), Tlv::AUTHORITY);
+    }
+
+    #[test]
+    fn v2_local() {
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        let (header, len) = complete(&buf);
+        assert_eq!(len, 16);
+        assert_eq!(header.source(), None);
+    }
+
+    #[test]
+    fn v2_invalid() {
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x11, 0x11, 0, 0]);
+        assert!(parse(&buf).is_err(), "unsupported version");
+
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x21, 0x11, 0, 4, 1, 2, 3, 4]);
+        assert!(parse(&buf).is_err(), "address block too short");
+
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x20, 0x00, 0, 4, Tlv::NOOP, 0, 2, 0]);
+        assert!(parse(&buf).is_err(), "truncated TLV");
+
+        // UDP over IPv4, and an unspecified transport.
+        for family in [0x12, 0x10] {
+            let mut buf = V2_SIGNATURE.to_vec();
+            buf.extend_from_slice(&[0x21, family, 0, 12]);
+            buf.extend_from_slice(&[192, 0, 2, 1, 198, 51, 100, 1, 0xdc, 0x04, 0x01, 0xbb]);
+            assert!(parse(&buf).is_err(), "unsupported transport {:#x}", family);
+        }
+    }
+
+    #[tokio::test]
+    async fn extract_header() {
+        test::get()
+            .success(&header(), |header| assert_eq!(header, None))
+            .await;
+
+        let header_value = Arc::new(ProxyHeader {
+            version: 2,
+            source: None,
+            destination: None,
+            tlvs: vec![Tlv {
+                kind: Tlv::UNIQUE_ID,
+                value: Bytes::from_static(b"abc"),
+            }],
+        });
+        test::get()
+            .proxy_header(Arc::clone(&header_value))
+            .success(&header(), |header: Option<Arc<ProxyHeader>>| {
+                assert_eq!(header.unwrap().unique_id(), Some(&b"abc"[..]));
+            })
+            .await;
+    }
+}
@@ -2,8 +2,11 @@
 
 use std::{
     mem,
-    net::SocketAddr,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 
@@ -13,9 +16,12 @@ use crate::{
     body,
     header::{HeaderMap, HeaderValue},
     method::Method,
+    proxy::ProxyHeader,
+    service::RequestStream,
     uri::Uri,
+    url::UrlFor,
     version::Version,
-    Body, Bytes,
+    Body, Bytes, PeerAddr,
 };
 
 pub(crate) type HyperRequest = hyper::Request<Body>;
@@ -27,7 +33,7 @@ pub struct Request {
     pub(crate) uri: Uri,
     pub(crate) version: Version,
     pub(crate) headers: HeaderMap,
-    pub(crate) remote_addr: SocketAddr,
+    pub(crate) connection: Connection,
 }
 
 impl Request {
@@ -45,11 +51,73 @@ impl Request {
     }
 }
 
+/// Data about the connection that a request was received on.
+#[derive(Clone, Debug)]
+pub(crate) struct Connection {
+    /// An identifier that is unique to this connection within the process.
+    pub(crate) id: u64,
+    pub(crate) local_addr: Option<PeerAddr>,
+    pub(crate) remote_addr: PeerAddr,
+    pub(crate) proxy_header: Option<Arc<ProxyHeader>>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<Arc<crate::tls::TlsInfo>>,
+}
+
+/// Returns the next connection [id](Connection::id).
+fn next_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Connection {
+    pub(crate) fn new(remote_addr: PeerAddr) -> Self {
+        Self {
+            id: next_id(),
+            local_addr: None,
+            remote_addr,
+            proxy_header: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    pub(crate) fn from_stream(stream: &impl RequestStream) -> Self {
+        Self {
+            id: next_id(),
+            local_addr: stream.local_addr(),
+            remote_addr: stream.remote_addr(),
+            proxy_header: stream.proxy_header(),
+            #[cfg(feature = "tls")]
+            tls: stream.tls_info(),
+        }
+    }
+
+    #[cfg(feature = "http3")]
+    pub(crate) fn from_quic(connection: &quinn::Connection, local_addr: Option<PeerAddr>) -> Self {
+        Self {
+            id: next_id(),
+            local_addr,
+            remote_addr: PeerAddr::Udp(connection.remote_address()),
+            proxy_header: None,
+            tls: Some(Arc::new(crate::tls::TlsInfo::quic(connection))),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RequestState {
     body: BodyState,
     pub(crate) current_path_index: usize,
     on_upgrade: Option<OnUpgrade>,
+    /// The origin that the client requested, set by
+    /// [`client_ip()`](crate::forwarded::client_ip).
+    pub(crate) origin: Option<String>,
+    /// The names and percent-decoded values of the parameters captured by a
+    /// [`Router`](crate::Router), with the most recent last.
+    pub(crate) params: Vec<(Arc<str>, String)>,
+    /// The named routes for [`url_for()`](crate::url::url_for), set by
+    /// [`url::mount()`](crate::url::mount) or a [`Router`](crate::Router).
+    pub(crate) url_for: Option<UrlFor>,
 }
 
 impl RequestState {
@@ -62,10 +130,12 @@ impl RequestState {
             },
             current_path_index: 0,
             on_upgrade,
+            origin: None,
+            params: Vec::new(),
+            url_for: None,
         }
     }
 
-    #[allow(dead_code)]
     pub(crate) fn on_upgrade(&mut self) -> Option<OnUpgrade> {
         self.on_upgrade.take()
     }
@@ -120,6 +190,55 @@ impl RequestState {
     pub(crate) fn end_current_path_index(&mut self, request: &Request) {
         self.current_path_index = request.full_path().len();
     }
+
+    /// Takes the body and upgrade of the request, including any part of the body that was
+    /// already read, leaving an empty body behind.
+    #[cfg(feature = "tower")]
+    pub(crate) fn take_body(&mut self) -> (Body, Option<OnUpgrade>) {
+        let state = mem::replace(
+            &mut self.body,
+            BodyState::Finished {
+                bytes: Vec::new(),
+                len: 0,
+            },
+        );
+        let body = match state {
+            BodyState::Pending { stream, bytes, .. } if bytes.is_empty() => stream,
+            BodyState::Pending { stream, bytes, .. } => {
+                use futures_util::{stream, StreamExt};
+
+                let read = stream::iter(bytes.into_iter().map(Ok::<_, hyper::Error>));
+                Body::wrap_stream(read.chain(stream))
+            }
+            BodyState::Finished { bytes, .. } => Body::from(bytes.concat()),
+            BodyState::Error => Body::empty(),
+        };
+        (body, self.on_upgrade.take())
+    }
+}
+
+/// The state of a request that is passed through a [`tower_service::Service`] by
+/// [`Filter::layer()`](crate::Filter::layer), which is restored by [`from_hyper()`] when the
+/// request reaches the [`Filter`](crate::Filter) again.
+#[cfg(feature = "tower")]
+#[derive(Debug)]
+pub(crate) struct Mounted {
+    pub(crate) connection: Connection,
+    pub(crate) current_path_index: usize,
+    pub(crate) origin: Option<String>,
+    pub(crate) params: Vec<(Arc<str>, String)>,
+    pub(crate) url_for: Option<UrlFor>,
+}
+
+#[cfg(feature = "tower")]
+impl Mounted {
+    fn restore(self, mut state: RequestState) -> (Connection, RequestState) {
+        state.current_path_index = self.current_path_index;
+        state.origin = self.origin;
+        state.params = self.params;
+        state.url_for = self.url_for;
+        (self.connection, state)
+    }
 }
 
 #[derive(Debug)]
@@ -136,10 +255,7 @@ enum BodyState {
     Error,
 }
 
-pub(crate) fn from_hyper(
-    request: HyperRequest,
-    remote_addr: SocketAddr,
-) -> (Request, RequestState) {
+pub(crate) fn from_hyper(request: HyperRequest, connection: Connection) -> (Request, RequestState) {
     let (
         Parts {
             method,
@@ -153,12 +269,17 @@ pub(crate) fn from_hyper(
     ) = request.into_parts();
 
     let state = RequestState::new(body, extensions.remove());
+    #[cfg(feature = "tower")]
+    let (connection, state) = match extensions.remove::<Mounted>() {
+        Some(mounted) => mounted.restore(state),
+        None => (connection, state),
+    };
     let request = Request {
         method,
         uri,
         version,
         headers,
-        remote_addr,
+        connection,
     };
     (request, state)
 }
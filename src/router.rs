@@ -0,0 +1,681 @@
+//! Routing with a prefix tree.
+//!
+//! A [`Router`] matches the path of a request against many routes at once. Patterns are made
+//! of literal segments, parameters such as `{id}`, and an optional catch-all such as `{*rest}`
+//! as the last segment. Each segment of the path is percent-decoded once, and then looked up in
+//! a tree of the segments of every pattern, instead of trying [`Filter`](crate::Filter)s one
+//! after another as [`or`](crate::Filter::or) does.
+//!
+//! Literal segments take priority over parameters, which take priority over catch-alls, so
+//! `/users/new` can be routed separately from `/users/{id}`. Parameters and catch-alls do not
+//! match empty segments, and catch-alls do not match `.` or `..` segments, even percent-encoded
+//! ones, so that they can be joined to a directory.
+//!
+//! # Example
+//!
+//! ```
+//! use myth::{router, Filter, Router};
+//!
+//! let router = Router::new()
+//!     .get("/", myth::any().handle(|| async { Ok("Home") }))
+//!     .get(
+//!         "/users/{id}",
+//!         router::param::<u64>("id").handle(|id: u64| async move { Ok(format!("User {}", id)) }),
+//!     )
+//!     .post("/users", myth::any().handle(|| async { Ok("Created") }))
+//!     .get(
+//!         "/static/{*path}",
+//!         router::param::<String>("path").handle(|path: String| async move { Ok(path) }),
+//!     );
+//!
+//! // Routers are `Filter`s, so they can be mounted under a prefix and combined.
+//! let filter = myth::path!("api" / ..).and(router);
+//! ```
+//!
+//! Routes can be [named](Router::name) to generate their URLs with
+//! [`url_for()`](crate::url::url_for).
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    future::{ready, Ready},
+    str::FromStr,
+    sync::Arc,
+};
+
+use futures_util::future::Either;
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    filter::{ready::ready_filter, FilterExecute, FilterSealed},
+    forward::AttemptedMethods,
+    impl_Filter,
+    method::Method,
+    outcome::{Outcome, RequestOutcome},
+    path::Redirect,
+    request::{Request, RequestState},
+    url::{Routes, UrlFor},
+    DynamicFilter, Filter, FilterBase, Forwarding, Responder, Response,
+};
+
+/// A [`Filter`] that routes requests by method and path pattern.
+///
+/// The path that has not been matched by previous [`Filter`]s is matched against the patterns,
+/// so a [`Router`] can be mounted under a prefix. The route that matches is executed after the
+/// whole path, and the [`param()`]s that it captures can be extracted from it. If no route
+/// matches the path, the [`Router`] forwards with [`Forwarding::NotFound`]. If several patterns
+/// match the path, the first one in priority order with a route for the method is executed, and
+/// if none of them has one, it forwards with [`Forwarding::MethodNotAllowed`] and the methods of
+/// all of their routes. Like [`end()`](crate::path::end), paths with a trailing slash are
+/// redirected to the path without it if that has a route.
+///
+/// See the [module documentation](self) for the syntax of patterns.
+#[derive(Clone, Debug, Default)]
+pub struct Router {
+    root: Node,
+    /// The names of routes, mounted for the matching route if there are any.
+    names: Arc<Routes>,
+    /// The pattern of the route that was added last, for naming it.
+    last: Option<Arc<[Segment]>>,
+}
+
+type Route = DynamicFilter<(), (Response,)>;
+
+/// The names and values of the parameters captured by a route.
+type Params = Vec<(Arc<str>, String)>;
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    /// Children for literal segments.
+    literals: HashMap<String, Node>,
+    /// The child for a parameter, and its name.
+    param: Option<(Arc<str>, Box<Node>)>,
+    /// The routes for a catch-all, and its name.
+    catch_all: Option<(Arc<str>, Endpoint)>,
+    /// The routes for patterns that end at this node.
+    endpoint: Option<Endpoint>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Endpoint {
+    routes: Vec<(Method, Route)>,
+}
+
+/// A segment of a route pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Literal(String),
+    Param(Arc<str>),
+    CatchAll(Arc<str>),
+}
+
+/// Parses a route pattern such as `/users/{id}/files/{*path}`.
+///
+/// # Panics
+///
+/// Panics if the pattern is invalid.
+pub(crate) fn parse_pattern(pattern: &str) -> Vec<Segment> {
+    let rest = pattern.strip_prefix('/').unwrap_or(pattern);
+    if rest.is_empty() {
+        return Vec::new();
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for segment in rest.split('/') {
+        if let Some(Segment::CatchAll(_)) = segments.last() {
+            panic!(
+                "invalid pattern {:?}: a catch-all must be the last segment",
+                pattern
+            );
+        }
+        let parsed = match segment
+            .strip_prefix('{')
+            .and_then(|segment| segment.strip_suffix('}'))
+        {
+            Some(name) => match name.strip_prefix('*') {
+                Some(name) => Segment::CatchAll(param_name(pattern, name)),
+                None => Segment::Param(param_name(pattern, name)),
+            },
+            None => {
+                assert!(
+                    !segment.is_empty(),
+                    "invalid pattern {:?}: segments cannot be empty",
+                    pattern
+                );
+                assert!(
+                    !segment.contains(&['{', '}'][..]),
+                    "invalid pattern {:?}: a parameter must be a whole segment",
+                    pattern
+                );
+                Segment::Literal(segment.to_owned())
+            }
+        };
+        if let Segment::Param(name) | Segment::CatchAll(name) = &parsed {
+            assert!(
+                !segments.iter().any(|segment| matches!(
+                    segment,
+                    Segment::Param(other) | Segment::CatchAll(other) if other == name
+                )),
+                "invalid pattern {:?}: duplicate parameter {:?}",
+                pattern,
+                name
+            );
+        }
+        segments.push(parsed);
+    }
+    segments
+}
+
+fn param_name(pattern: &str, name: &str) -> Arc<str> {
+    assert!(
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_'),
+        "invalid pattern {:?}: invalid parameter name {:?}",
+        pattern,
+        name
+    );
+    name.into()
+}
+
+/// Splits a path into its percent-decoded segments, and the indices that they start at.
+pub(crate) fn split_path(path: &str) -> Vec<(usize, Cow<'_, str>)> {
+    let (mut start, rest) = match path.strip_prefix('/') {
+        Some(rest) => (1, rest),
+        None => (0, path),
+    };
+    if rest.is_empty() {
+        return Vec::new();
+    }
+    rest.split('/')
+        .map(|segment| {
+            let decoded = (start, percent_decode_str(segment).decode_utf8_lossy());
+            start += segment.len() + 1;
+            decoded
+        })
+        .collect()
+}
+
+impl Router {
+    /// Creates a new [`Router`] without routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route for requests with `method` and a path that matches `pattern`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is invalid, if it names a parameter differently than another
+    /// pattern at the same position, or if there already is a route for the method and pattern.
+    pub fn route<F, R>(mut self, method: Method, pattern: &str, filter: F) -> Self
+    where
+        F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+        R: Responder + Send + 'static,
+    {
+        let route = filter
+            .handle(|responder: R| async move { Ok(responder.into_response()) })
+            .dynamic();
+        let segments = parse_pattern(pattern);
+        self.last = Some(segments.clone().into());
+        let endpoint = self.root.endpoint_mut(pattern, segments);
+        assert!(
+            !endpoint.routes.iter().any(|(other, _)| *other == method),
+            "duplicate route {} {:?}",
+            method,
+            pattern
+        );
+        endpoint.routes.push((method, route));
+        self
+    }
+
+    /// Names the route that was added last, to generate its URL with
+    /// [`UrlFor`](crate::url::UrlFor).
+    ///
+    /// The routes of a [`Router`] with names mount them with [`url::mount()`](crate::url::mount)
+    /// under the path that the [`Router`] is mounted under.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no route has been added, or if the name is already used.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        let segments = self
+            .last
+            .clone()
+            .expect("a route must be added before it is named");
+        Arc::make_mut(&mut self.names).insert(name.into(), segments);
+        self
+    }
+}
+
+// rustfmt indents the `concat!` further on every run.
+#[rustfmt::skip]
+macro_rules! define_route_method {
+    ($fn_name:ident $const_name:ident) => {
+        impl Router {
+            #[doc = concat!(
+                        "Adds a route for [`",
+                        stringify!($const_name),
+                        "`](Method::",
+                        stringify!($const_name),
+                        ") requests with a path that matches `pattern`.\n\n",
+                        "# Panics\n\n",
+                        "Panics under the same conditions as [`route()`](Self::route)."
+                    )]
+            pub fn $fn_name<F, R>(self, pattern: &str, filter: F) -> Self
+            where
+                F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+                R: Responder + Send + 'static,
+            {
+                self.route(Method::$const_name, pattern, filter)
+            }
+        }
+    };
+}
+
+all_methods!(define_route_method);
+
+impl Node {
+    fn endpoint_mut(&mut self, pattern: &str, segments: Vec<Segment>) -> &mut Endpoint {
+        let mut node = self;
+        for segment in segments {
+            node = match segment {
+                Segment::Literal(literal) => node.literals.entry(literal).or_default(),
+                Segment::Param(name) => {
+                    let (existing, child) = node
+                        .param
+                        .get_or_insert_with(|| (Arc::clone(&name), Box::default()));
+                    assert_conflicting(pattern, existing, &name);
+                    child
+                }
+                Segment::CatchAll(name) => {
+                    let (existing, endpoint) = node
+                        .catch_all
+                        .get_or_insert_with(|| (Arc::clone(&name), Endpoint::default()));
+                    assert_conflicting(pattern, existing, &name);
+                    return endpoint;
+                }
+            };
+        }
+        node.endpoint.get_or_insert_with(Endpoint::default)
+    }
+
+    /// Finds the endpoints for the segments of `path` in priority order, with the parameters
+    /// that each of them captures.
+    fn find<'n>(
+        &'n self,
+        path: &str,
+        segments: &[(usize, Cow<'_, str>)],
+        params: &mut Params,
+        found: &mut Vec<(&'n Endpoint, Params)>,
+    ) {
+        let ((start, segment), rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                if let Some(endpoint) = &self.endpoint {
+                    found.push((endpoint, params.clone()));
+                }
+                return;
+            }
+        };
+
+        if let Some(child) = self.literals.get(segment.as_ref()) {
+            child.find(path, rest, params, found);
+        }
+
+        if let (Some((name, child)), false) = (&self.param, segment.is_empty()) {
+            params.push((Arc::clone(name), segment.clone().into_owned()));
+            child.find(path, rest, params, found);
+            params.pop();
+        }
+
+        if let Some((name, endpoint)) = &self.catch_all {
+            if segment.is_empty() && rest.is_empty() {
+                return;
+            }
+            let value = percent_decode_str(&path[*start..]).decode_utf8_lossy();
+            if value
+                .split('/')
+                .any(|segment| segment == "." || segment == "..")
+            {
+                return;
+            }
+            let mut params = params.clone();
+            params.push((Arc::clone(name), value.into_owned()));
+            found.push((endpoint, params));
+        }
+    }
+}
+
+fn assert_conflicting(pattern: &str, existing: &str, name: &str) {
+    assert!(
+        existing == name,
+        "invalid pattern {:?}: parameter {:?} conflicts with {:?} of another route",
+        pattern,
+        name,
+        existing
+    );
+}
+
+impl Endpoint {
+    fn attempted(&self) -> AttemptedMethods {
+        self.routes
+            .iter()
+            .fold(AttemptedMethods::NONE, |attempted, (method, _)| {
+                attempted | AttemptedMethods::from_method(method)
+            })
+    }
+}
+
+impl Router {
+    /// Returns whether any route matches the segments of `path`.
+    fn has_route(&self, path: &str, segments: &[(usize, Cow<'_, str>)]) -> bool {
+        let mut found = Vec::new();
+        self.root.find(path, segments, &mut Vec::new(), &mut found);
+        !found.is_empty()
+    }
+}
+
+impl FilterSealed for Router {}
+
+impl<'f> FilterBase<'f> for Router {
+    type Input = ();
+
+    type Success = (Response,);
+}
+
+impl<'f> FilterExecute<'f> for Router {
+    type Future = Either<
+        Ready<RequestOutcome<Self::Input, Self::Success>>,
+        <Route as FilterExecute<'f>>::Future,
+    >;
+
+    fn execute(
+        &'f self,
+        request: &'f Request,
+        mut request_state: RequestState,
+        (): Self::Input,
+    ) -> Self::Future {
+        let path = request_state.current_path(request);
+        let segments = split_path(path);
+        let mut found = Vec::new();
+        self.root.find(path, &segments, &mut Vec::new(), &mut found);
+
+        let route = found.iter().find_map(|(endpoint, params)| {
+            endpoint
+                .routes
+                .iter()
+                .find(|(method, _)| *method == request.method)
+                .map(|(_, route)| (route, params))
+        });
+        if let Some((route, params)) = route {
+            if !self.names.is_empty() {
+                let prefix = request_state.previous_path(request).trim_end_matches('/');
+                request_state.url_for = Some(UrlFor::new(Arc::clone(&self.names), prefix));
+            }
+            request_state.end_current_path_index(request);
+            request_state.params.extend(params.iter().cloned());
+            return Either::Right(route.execute(request, request_state, ()));
+        }
+
+        let outcome = if !found.is_empty() {
+            let attempted = found
+                .iter()
+                .fold(AttemptedMethods::NONE, |attempted, (endpoint, _)| {
+                    attempted | endpoint.attempted()
+                });
+            Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::MethodNotAllowed(attempted),
+            }
+        } else {
+            match segments.split_last() {
+                Some(((_, last), rest)) if last.is_empty() && self.has_route(path, rest) => {
+                    let location = format!(
+                        "{}{}{}",
+                        request_state.origin.as_deref().unwrap_or(""),
+                        request_state.previous_path(request),
+                        &path[..path.len() - 1],
+                    );
+                    Outcome::Error(Redirect::new(location).into())
+                }
+                _ => Outcome::Forward {
+                    input: (),
+                    forwarding: Forwarding::NotFound,
+                },
+            }
+        };
+        Either::Left(ready(RequestOutcome {
+            request_state,
+            outcome,
+        }))
+    }
+}
+
+/// Creates a [`Filter`] that extracts the parameter `name` captured by a [`Router`], forwarding
+/// if it was not captured or cannot be parsed.
+///
+/// Parameters are percent-decoded, and catch-alls include the slashes between their segments.
+pub fn param<T>(name: impl Into<String>) -> impl_Filter!(T => Clone + (fmt::Debug))
+where
+    T: FromStr + Send + 'static,
+{
+    let name = name.into();
+    ready_filter(move |_, request_state| {
+        match request_state
+            .params
+            .iter()
+            .rev()
+            .find(|(param, _)| **param == *name)
+            .and_then(|(_, value)| value.parse().ok())
+        {
+            Some(value) => Outcome::Success((value,)),
+            None => Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::NotFound,
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{param, parse_pattern, Router, Segment};
+    use crate::{
+        filter::ready::ready_filter,
+        forward::AttemptedMethods,
+        forwarded::{client_ip, ForwardedConfig},
+        header,
+        outcome::Outcome,
+        path::Redirect,
+        test, Filter, Forwarding, Responder, StatusCode,
+    };
+
+    fn router() -> Router {
+        Router::new()
+            .get("/", crate::any().handle(|| async { Ok("root") }))
+            .get("/users/new", crate::any().handle(|| async { Ok("new") }))
+            .get(
+                "/users/{id}",
+                param::<u64>("id").handle(|id: u64| async move { Ok(format!("user {}", id)) }),
+            )
+            .delete(
+                "/users/{id}",
+                param::<u64>("id").handle(|id: u64| async move { Ok(format!("deleted {}", id)) }),
+            )
+            .get(
+                "/users/{id}/posts/{post}",
+                param::<u64>("id").and(param::<String>("post")).handle(
+                    |id: u64, post: String| async move { Ok(format!("post {} by {}", post, id)) },
+                ),
+            )
+            .get(
+                "/files/{*path}",
+                param::<String>("path").handle(|path: String| async move { Ok(path) }),
+            )
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(parse_pattern("/"), []);
+        assert_eq!(
+            parse_pattern("/a/{b}/{*c}"),
+            [
+                Segment::Literal("a".to_owned()),
+                Segment::Param("b".into()),
+                Segment::CatchAll("c".into()),
+            ]
+        );
+        for invalid in [
+            "/a//b", "/a/", "/{*a}/b", "/a{b}", "/{}", "/{a-b}", "/{a}/{a}",
+        ] {
+            assert!(
+                std::panic::catch_unwind(|| parse_pattern(invalid)).is_err(),
+                "{:?} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn routes() {
+        let router = router();
+        for (uri, body) in [
+            ("/", "root"),
+            ("/users/new", "new"),
+            ("/users/5", "user 5"),
+            ("/users/5/posts/hello%20world", "post hello world by 5"),
+            ("/files/a/b%2Fc.txt", "a/b/c.txt"),
+        ] {
+            let response = test::get().uri(uri).response(&router).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            assert_eq!(response.body(), body, "{}", uri);
+        }
+
+        let response = test::delete().uri("/users/5").response(&router).await;
+        assert_eq!(response.body(), "deleted 5");
+
+        for uri in [
+            "/users",
+            "/users/5/posts",
+            "/files",
+            "/other",
+            "/users/five",
+            "/files/a/../secret",
+            "/files/..%2Fsecret",
+            "/files/a/%2E%2E",
+            "/files/.",
+        ] {
+            test::get().uri(uri).not_found(&router).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn params_after_forward() {
+        let filter = router()
+            .or(param::<String>("id").handle(|id: String| async move { Ok(id.into_response()) }));
+        // The router captures `id` before its route forwards, which must not leak into `or`.
+        test::get().uri("/users/five").not_found(&filter).await;
+
+        // Neither must the origin from `client_ip()` or the routes mounted by the router.
+        let first = client_ip(ForwardedConfig::new().trusted(["10.0.0.0/8".parse().unwrap()]))
+            .and(router().name("files"))
+            .handle(|_, response| async move { Ok(response) });
+        let second = ready_filter(|_, request_state| {
+            Outcome::Success((format!(
+                "{:?} {}",
+                request_state.origin,
+                request_state.url_for.is_some()
+            ),))
+        })
+        .handle(|state: String| async move { Ok(state.into_response()) });
+        let response = test::get()
+            .uri("/users/five")
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Host", "example.com")
+            .header("X-Forwarded-Proto", "https")
+            .response(&first.or(second))
+            .await;
+        assert_eq!(response.body(), "None false");
+    }
+
+    #[tokio::test]
+    async fn method_not_allowed() {
+        let router = router();
+        match test::post().uri("/users/5").forwarding(&router).await {
+            Forwarding::MethodNotAllowed(attempted) => {
+                assert_eq!(attempted, AttemptedMethods::GET | AttemptedMethods::DELETE);
+            }
+            forwarding => panic!("unexpected forwarding {:?}", forwarding),
+        }
+
+        // A literal route and a parameter route that overlap, with different methods.
+        let overlapping = Router::new()
+            .get("/users/new", crate::any().handle(|| async { Ok("new") }))
+            .delete(
+                "/users/{name}",
+                param::<String>("name")
+                    .handle(|name: String| async move { Ok(format!("deleted {}", name)) }),
+            );
+        let response = test::delete()
+            .uri("/users/new")
+            .response(&overlapping)
+            .await;
+        assert_eq!(response.body(), "deleted new");
+        match test::post()
+            .uri("/users/new")
+            .forwarding(&overlapping)
+            .await
+        {
+            Forwarding::MethodNotAllowed(attempted) => {
+                assert_eq!(attempted, AttemptedMethods::GET | AttemptedMethods::DELETE);
+            }
+            forwarding => panic!("unexpected forwarding {:?}", forwarding),
+        }
+
+        let filter = router.or(crate::path!("users" / u64)
+            .and(crate::method::put())
+            .handle(|id: u64| async move { Ok(id.to_string().into_response()) }));
+        let response = test::post()
+            .uri("/users/5")
+            .forwarding(&filter)
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET, PUT, DELETE");
+        let response = test::put().uri("/users/5").response(&filter).await;
+        assert_eq!(response.body(), "5");
+    }
+
+    #[tokio::test]
+    async fn trailing_slash() {
+        let redirect: Redirect = test::get().uri("/users/5/").error(&router()).await;
+        assert_eq!(redirect.location(), "/users/5");
+        test::get().uri("/other/").not_found(&router()).await;
+    }
+
+    #[tokio::test]
+    async fn mounted() {
+        let filter = crate::path!("api" / ..).and(router());
+        let response = test::get().uri("/api/users/5").response(&filter).await;
+        assert_eq!(response.body(), "user 5");
+        let response = test::get().uri("/api").response(&filter).await;
+        assert_eq!(response.body(), "root");
+        let redirect: Redirect = test::get().uri("/api/users/5/").error(&filter).await;
+        assert_eq!(redirect.location(), "/api/users/5");
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate route")]
+    fn duplicate_route() {
+        router().get("/users/{id}", crate::any().handle(|| async { Ok("") }));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicts")]
+    fn conflicting_params() {
+        router().put("/users/{name}", crate::any().handle(|| async { Ok("") }));
+    }
+}
@@ -0,0 +1,379 @@
+//! Redirects from HTTP to HTTPS.
+//!
+//! The [`https_redirect()`] filter redirects requests made over plain HTTP to the same path and
+//! query over HTTPS, and forwards requests made over TLS. A single [`Server`](crate::Server)
+//! can then listen for both HTTPS and HTTP, and shut both down with one signal.
+//!
+//! Behind a proxy that terminates TLS, every request arrives over plain HTTP. Put
+//! [`forwarded::client_ip()`](crate::forwarded::client_ip) before [`https_redirect()`] so that
+//! requests that trusted proxies report as `https` are forwarded instead of redirected.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use myth::{security::https_redirect, Filter};
+//!
+//! # #[cfg(feature = "tls")]
+//! # #[tokio::main] async fn main() {
+//! let app = myth::any().handle(|| async { Ok(myth::Responder::into_response("Hello world!")) });
+//!
+//! let config = https_redirect::Config::new().acme_challenge_dir("/var/www/acme");
+//! let filter = https_redirect(config).or(app);
+//!
+//! let tls = myth::TlsConfig::read_file("/path/to/certificate.pem", "/path/to/private/key.pem");
+//! myth::serve(filter)
+//!     .bind(([0, 0, 0, 0], 443))
+//!     .with_tls(tls)
+//!     .and_bind(([0, 0, 0, 0], 80))
+//!     .run()
+//!     .await;
+//! # }
+//! # #[cfg(not(feature = "tls"))] fn main() {}
+//! ```
+
+use std::{convert::TryFrom, fmt, path::PathBuf};
+
+use hyper::http::uri::Authority;
+
+use crate::{
+    filter::ready::ready_filter,
+    header::{self, HeaderValue},
+    impl_Filter,
+    outcome::Outcome,
+    request::Request,
+    response::default_response,
+    Filter, Forwarding, Responder, Response, StatusCode,
+};
+
+/// The path prefix of HTTP-01 challenges from ACME certificate authorities.
+const ACME_CHALLENGE: &str = "/.well-known/acme-challenge/";
+
+/// A configuration for [`https_redirect()`].
+#[derive(Clone, Debug)]
+pub struct Config {
+    port: u16,
+    status: StatusCode,
+    host: Option<String>,
+    acme_challenge_dir: Option<PathBuf>,
+}
+
+impl Config {
+    /// Creates a new configuration, which redirects to port 443 with
+    /// `308 Permanent Redirect`.
+    pub fn new() -> Self {
+        Self {
+            port: 443,
+            status: StatusCode::PERMANENT_REDIRECT,
+            host: None,
+            acme_challenge_dir: None,
+        }
+    }
+
+    /// Sets the port that HTTPS is served on.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets the status of redirects, such as `301 Moved Permanently` for old clients that do
+    /// not support `308 Permanent Redirect`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not a redirection (`3xx`) status.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        assert!(
+            status.is_redirection(),
+            "{} is not a redirection status",
+            status
+        );
+        self.status = status;
+        self
+    }
+
+    /// Redirects to `host` instead of the host that the client requested.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `host` is not a valid host, or if it has a port, which is set with
+    /// [`port()`](Self::port) instead.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        let authority = host
+            .parse::<Authority>()
+            .unwrap_or_else(|_| panic!("invalid host {:?}", host));
+        assert!(
+            authority.port().is_none(),
+            "host {:?} must not have a port, set it with `port()` instead",
+            host
+        );
+        self.host = Some(host);
+        self
+    }
+
+    /// Serves HTTP-01 challenges from ACME certificate authorities, such as Let's Encrypt,
+    /// from files in `dir`.
+    ///
+    /// Requests for `/.well-known/acme-challenge/<token>` over plain HTTP get the contents of
+    /// the file named `<token>`, instead of a redirect.
+    pub fn acme_challenge_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.acme_challenge_dir = Some(dir.into());
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What to respond with to a request over plain HTTP.
+#[derive(Debug)]
+enum Action {
+    Redirect {
+        status: StatusCode,
+        location: String,
+    },
+    Challenge(PathBuf),
+    NotFound,
+    BadRequest,
+}
+
+/// Creates a [`Filter`] that redirects requests made over plain HTTP to HTTPS, keeping the path
+/// and query, and forwards requests made over TLS.
+///
+/// Requests that trusted proxies report were made over `https`, through
+/// [`forwarded::client_ip()`](crate::forwarded::client_ip), are also forwarded.
+///
+/// Requests without a valid `Host` get `400 Bad Request`, unless a [host](Config::host) is
+/// configured.
+pub fn https_redirect(config: Config) -> impl_Filter!(Response => Clone + (fmt::Debug)) {
+    ready_filter(move |request, request_state| {
+        let forwarded_https = request_state
+            .origin
+            .as_deref()
+            .is_some_and(|origin| origin.starts_with("https://"));
+        if is_tls(request) || forwarded_https {
+            return Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::NotFound,
+            };
+        }
+        Outcome::Success((action(request, &config),))
+    })
+    .handle(|action| async move {
+        Ok(match action {
+            Action::Redirect { status, location } => match HeaderValue::try_from(location) {
+                Ok(location) => default_response(status).with_header(header::LOCATION, location),
+                Err(_) => default_response(StatusCode::BAD_REQUEST),
+            },
+            Action::Challenge(path) => match tokio::fs::read(&path).await {
+                Ok(contents) => contents.into_response(),
+                Err(error) => {
+                    tracing::debug!(
+                        "Failed to read ACME challenge {}: {}",
+                        path.display(),
+                        error
+                    );
+                    default_response(StatusCode::NOT_FOUND)
+                }
+            },
+            Action::NotFound => default_response(StatusCode::NOT_FOUND),
+            Action::BadRequest => default_response(StatusCode::BAD_REQUEST),
+        })
+    })
+}
+
+#[cfg(feature = "tls")]
+fn is_tls(request: &Request) -> bool {
+    request.connection.tls.is_some()
+}
+
+#[cfg(not(feature = "tls"))]
+fn is_tls(_: &Request) -> bool {
+    false
+}
+
+fn action(request: &Request, config: &Config) -> Action {
+    let path = request.uri.path();
+    if let (Some(dir), Some(token)) = (
+        &config.acme_challenge_dir,
+        path.strip_prefix(ACME_CHALLENGE),
+    ) {
+        return if is_token(token) {
+            Action::Challenge(dir.join(token))
+        } else {
+            Action::NotFound
+        };
+    }
+
+    let host = match &config.host {
+        Some(host) => host.clone(),
+        None => {
+            let authority = request
+                .header(header::HOST)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|host| host.parse::<Authority>().ok())
+                .or_else(|| request.uri.authority().cloned());
+            match authority {
+                Some(authority) => authority.host().to_owned(),
+                None => return Action::BadRequest,
+            }
+        }
+    };
+    let path_and_query = request
+        .uri
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+    let location = match config.port {
+        443 => format!("https://{}{}", host, path_and_query),
+        port => format!("https://{}:{}{}", host, port, path_and_query),
+    };
+    Action::Redirect {
+        status: config.status,
+        location,
+    }
+}
+
+/// Returns whether `token` only has the base64url characters that ACME tokens are made of, so
+/// that it cannot name a file outside of the challenge directory.
+fn is_token(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{https_redirect, Config};
+    use crate::{
+        forwarded::{client_ip, ForwardedConfig},
+        test, Filter, StatusCode,
+    };
+
+    #[tokio::test]
+    async fn redirect() {
+        let response = test::get()
+            .uri("/path?query=1")
+            .header("Host", "example.com:8080")
+            .response(&https_redirect(Config::new()))
+            .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()["Location"],
+            "https://example.com/path?query=1"
+        );
+
+        let config = Config::new()
+            .port(8443)
+            .status(StatusCode::MOVED_PERMANENTLY);
+        let response = test::get()
+            .uri("/")
+            .header("Host", "example.com")
+            .response(&https_redirect(config))
+            .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()["Location"], "https://example.com:8443/");
+    }
+
+    #[tokio::test]
+    async fn host() {
+        let filter = https_redirect(Config::new());
+        let response = test::get().uri("/").response(&filter).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let filter = https_redirect(Config::new().host("example.com"));
+        let response = test::get()
+            .uri("/")
+            .header("Host", "attacker.example")
+            .response(&filter)
+            .await;
+        assert_eq!(response.headers()["Location"], "https://example.com/");
+    }
+
+    #[test]
+    #[should_panic(expected = "must not have a port")]
+    fn host_with_port() {
+        Config::new().host("example.com:8443");
+    }
+
+    #[tokio::test]
+    async fn behind_proxy() {
+        let filter = client_ip(ForwardedConfig::new().trusted(["10.0.0.0/8".parse().unwrap()]))
+            .and(https_redirect(Config::new()))
+            .handle(|_, response| async move { Ok(response) });
+
+        test::get()
+            .uri("/")
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Host", "example.com")
+            .header("X-Forwarded-Proto", "https")
+            .not_found(&filter)
+            .await;
+
+        // Untrusted peers cannot claim to have used HTTPS.
+        let response = test::get()
+            .uri("/")
+            .remote_addr(([192, 0, 2, 1], 1234))
+            .header("Host", "example.com")
+            .header("X-Forwarded-Proto", "https")
+            .response(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+        let response = test::get()
+            .uri("/")
+            .remote_addr(([10, 0, 0, 2], 1234))
+            .header("Host", "example.com")
+            .header("X-Forwarded-Proto", "http")
+            .response(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+
+    #[test]
+    #[should_panic]
+    fn status_not_redirection() {
+        Config::new().status(StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn acme_challenge() {
+        let dir = std::env::temp_dir().join(format!("myth-acme-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("token_1-a"), "token_1-a.thumbprint").unwrap();
+        let filter = https_redirect(Config::new().acme_challenge_dir(&dir));
+
+        let response = test::get()
+            .uri("/.well-known/acme-challenge/token_1-a")
+            .header("Host", "example.com")
+            .response(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "token_1-a.thumbprint");
+
+        for uri in [
+            "/.well-known/acme-challenge/missing",
+            "/.well-known/acme-challenge/..%2Fsecret",
+            "/.well-known/acme-challenge/",
+        ] {
+            let response = test::get()
+                .uri(uri)
+                .header("Host", "example.com")
+                .response(&filter)
+                .await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        let response = test::get()
+            .uri("/other")
+            .header("Host", "example.com")
+            .response(&filter)
+            .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 //! Security
 
 pub mod hsts;
+pub mod https_redirect;
 pub mod origin;
 
 use std::{
@@ -17,6 +18,8 @@ use crate::{
     FilterBase, Responder, Response,
 };
 
+pub use self::https_redirect::https_redirect;
+
 static NOSNIFF: HeaderValue = HeaderValue::from_static("nosniff");
 
 /// Sets `X-Content-Type-Options: nosniff`
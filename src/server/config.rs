@@ -0,0 +1,326 @@
+use std::{error::Error as StdError, time::Duration};
+
+use hyper::{header::HeaderValue, server::Builder};
+
+use crate::service::{Incoming, RequestStream};
+
+/// HTTP/1 options for a [`Server`](crate::Server), set with
+/// [`Server::http1()`](crate::Server::http1).
+///
+/// Options that are not set use the defaults of [`hyper`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use myth::Http1Config;
+///
+/// let config = Http1Config::new()
+///     .keep_alive(true)
+///     .header_read_timeout(Duration::from_secs(10));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Http1Config {
+    keep_alive: Option<bool>,
+    half_close: Option<bool>,
+    header_read_timeout: Option<Duration>,
+    max_buf_size: Option<usize>,
+    title_case_headers: Option<bool>,
+}
+
+impl Http1Config {
+    /// Creates a new configuration with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether connections are kept alive between requests.
+    ///
+    /// Defaults to `true`.
+    pub fn keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Sets whether connections support half-closures, where the client shuts down its write side
+    /// while still waiting for a response.
+    ///
+    /// Defaults to `false`.
+    pub fn half_close(mut self, half_close: bool) -> Self {
+        self.half_close = Some(half_close);
+        self
+    }
+
+    /// Sets a timeout for reading the headers of a request. Connections that do not send their
+    /// headers in time are closed.
+    ///
+    /// By default, there is no timeout.
+    pub fn header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.header_read_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum size of the read and write buffers of a connection.
+    ///
+    /// Defaults to about 400 KB.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is less than 8192.
+    pub fn max_buf_size(mut self, max: usize) -> Self {
+        assert!(max >= 8192, "the maximum buffer size must be at least 8192");
+        self.max_buf_size = Some(max);
+        self
+    }
+
+    /// Sets whether response header names are written in title case, such as `Content-Length`.
+    ///
+    /// Defaults to `false`.
+    pub fn title_case_headers(mut self, title_case_headers: bool) -> Self {
+        self.title_case_headers = Some(title_case_headers);
+        self
+    }
+
+    fn apply<I>(&self, mut builder: Builder<I>) -> Builder<I> {
+        if let Some(keep_alive) = self.keep_alive {
+            builder = builder.http1_keepalive(keep_alive);
+        }
+        if let Some(half_close) = self.half_close {
+            builder = builder.http1_half_close(half_close);
+        }
+        if let Some(timeout) = self.header_read_timeout {
+            builder = builder.http1_header_read_timeout(timeout);
+        }
+        if let Some(max) = self.max_buf_size {
+            builder = builder.http1_max_buf_size(max);
+        }
+        if let Some(title_case_headers) = self.title_case_headers {
+            builder = builder.http1_title_case_headers(title_case_headers);
+        }
+        builder
+    }
+}
+
+/// HTTP/2 options for a [`Server`](crate::Server), set with
+/// [`Server::http2()`](crate::Server::http2).
+///
+/// Options that are not set use the defaults of [`hyper`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use myth::Http2Config;
+///
+/// let config = Http2Config::new()
+///     .max_concurrent_streams(100)
+///     .keep_alive_interval(Duration::from_secs(30));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Http2Config {
+    initial_stream_window_size: Option<u32>,
+    initial_connection_window_size: Option<u32>,
+    adaptive_window: Option<bool>,
+    max_frame_size: Option<u32>,
+    max_concurrent_streams: Option<u32>,
+    keep_alive_interval: Option<Duration>,
+    keep_alive_timeout: Option<Duration>,
+}
+
+impl Http2Config {
+    /// Creates a new configuration with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial flow control window size of each stream, in bytes.
+    ///
+    /// Defaults to 1 MB.
+    pub fn initial_stream_window_size(mut self, size: u32) -> Self {
+        self.initial_stream_window_size = Some(size);
+        self
+    }
+
+    /// Sets the initial flow control window size of each connection, in bytes.
+    ///
+    /// Defaults to 1 MB.
+    pub fn initial_connection_window_size(mut self, size: u32) -> Self {
+        self.initial_connection_window_size = Some(size);
+        self
+    }
+
+    /// Sets whether the flow control window sizes are adjusted based on an estimate of the
+    /// bandwidth-delay product. This overrides the initial window sizes.
+    ///
+    /// Defaults to `false`.
+    pub fn adaptive_window(mut self, adaptive_window: bool) -> Self {
+        self.adaptive_window = Some(adaptive_window);
+        self
+    }
+
+    /// Sets the maximum frame size, in bytes.
+    ///
+    /// Defaults to 16 KB.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than 16,384 or greater than 16,777,215.
+    pub fn max_frame_size(mut self, size: u32) -> Self {
+        assert!(
+            (16_384..=16_777_215).contains(&size),
+            "the maximum frame size must be between 16,384 and 16,777,215"
+        );
+        self.max_frame_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum number of concurrent streams on each connection.
+    ///
+    /// Defaults to 200.
+    pub fn max_concurrent_streams(mut self, max: u32) -> Self {
+        self.max_concurrent_streams = Some(max);
+        self
+    }
+
+    /// Sets the interval at which `PING` frames are sent to keep connections alive.
+    ///
+    /// By default, no `PING` frames are sent.
+    pub fn keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets how long to wait for an acknowledgement of a keep-alive `PING` before closing the
+    /// connection. This has no effect unless [`keep_alive_interval`](Self::keep_alive_interval)
+    /// is set.
+    ///
+    /// Defaults to 20 seconds.
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    fn apply<I>(&self, mut builder: Builder<I>) -> Builder<I> {
+        if let Some(size) = self.initial_stream_window_size {
+            builder = builder.http2_initial_stream_window_size(size);
+        }
+        if let Some(size) = self.initial_connection_window_size {
+            builder = builder.http2_initial_connection_window_size(size);
+        }
+        if let Some(adaptive_window) = self.adaptive_window {
+            builder = builder.http2_adaptive_window(adaptive_window);
+        }
+        if let Some(size) = self.max_frame_size {
+            builder = builder.http2_max_frame_size(size);
+        }
+        if let Some(max) = self.max_concurrent_streams {
+            builder = builder.http2_max_concurrent_streams(max);
+        }
+        if let Some(interval) = self.keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.keep_alive_timeout {
+            builder = builder.http2_keep_alive_timeout(timeout);
+        }
+        builder
+    }
+}
+
+/// The HTTP versions that a [`Server`](crate::Server) accepts.
+#[derive(Clone, Copy, Debug)]
+enum Protocols {
+    Both,
+    Http1Only,
+    Http2Only,
+}
+
+/// The protocol options of a [`Server`](crate::Server).
+#[derive(Clone, Debug)]
+pub(crate) struct Config {
+    protocols: Protocols,
+    pub(crate) http1: Http1Config,
+    pub(crate) http2: Http2Config,
+    pub(crate) shutdown_timeout: Option<Duration>,
+    /// The options that are set on every TCP listener.
+    pub(crate) tcp_nodelay: bool,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    /// The QUIC config for HTTP/3, with the certificates of the
+    /// [`TlsConfig`](crate::TlsConfig) that the server uses.
+    #[cfg(feature = "http3")]
+    pub(crate) quic: Option<quinn::ServerConfig>,
+    /// The HTTP/3 listeners.
+    #[cfg(feature = "http3")]
+    pub(crate) http3: Vec<quinn::Endpoint>,
+}
+
+impl Config {
+    pub(crate) fn http1_only(&mut self) {
+        self.protocols = Protocols::Http1Only;
+    }
+
+    pub(crate) fn http2_only(&mut self) {
+        self.protocols = Protocols::Http2Only;
+    }
+
+    /// Creates a [`Builder`] for a [`hyper` server](hyper::Server) with these options, which
+    /// are also set on the TCP listeners of `incoming`.
+    pub(crate) fn builder<I>(&self, mut incoming: I) -> Builder<I>
+    where
+        I: Incoming,
+        I::Conn: RequestStream,
+        I::Error: StdError + Send + Sync + 'static,
+    {
+        incoming.set_tcp_options(self.tcp_nodelay, self.tcp_keepalive);
+        let builder = hyper::Server::builder(incoming);
+        let builder = match self.protocols {
+            Protocols::Both => builder,
+            Protocols::Http1Only => builder.http1_only(true),
+            Protocols::Http2Only => builder.http2_only(true),
+        };
+        let builder = self.http1.apply(builder);
+        self.http2.apply(builder)
+    }
+
+    /// Returns the `Alt-Svc` header that advertises the HTTP/3 listeners, if there are any.
+    pub(crate) fn alt_svc(&self) -> Option<HeaderValue> {
+        #[cfg(feature = "http3")]
+        {
+            let mut ports = self
+                .http3
+                .iter()
+                .filter_map(|endpoint| endpoint.local_addr().ok())
+                .map(|addr| addr.port())
+                .collect::<Vec<_>>();
+            ports.sort_unstable();
+            ports.dedup();
+            if !ports.is_empty() {
+                let alt_svc = ports
+                    .iter()
+                    .map(|port| format!("h3=\":{}\"; ma=86400", port))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return HeaderValue::try_from(alt_svc).ok();
+            }
+        }
+        None
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            protocols: Protocols::Both,
+            http1: Http1Config::default(),
+            http2: Http2Config::default(),
+            shutdown_timeout: None,
+            tcp_nodelay: false,
+            tcp_keepalive: None,
+            #[cfg(feature = "http3")]
+            quic: None,
+            #[cfg(feature = "http3")]
+            http3: Vec::new(),
+        }
+    }
+}
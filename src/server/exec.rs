@@ -0,0 +1,44 @@
+use std::future::{pending, Future};
+
+use futures_util::{future::select, pin_mut};
+use hyper::rt::Executor;
+use tokio::sync::watch;
+
+/// An [`Executor`] for the connections of a server, which drops them all once the server is
+/// closed.
+#[derive(Clone, Debug)]
+pub(crate) struct Exec {
+    closed: watch::Receiver<bool>,
+}
+
+impl Exec {
+    /// Creates a new [`Exec`], and a [`watch::Sender`] that closes its connections when `true`
+    /// is sent.
+    pub(crate) fn new() -> (watch::Sender<bool>, Self) {
+        let (close, closed) = watch::channel(false);
+        (close, Self { closed })
+    }
+}
+
+impl<F> Executor<F> for Exec
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, future: F) {
+        let closed = wait_closed(self.closed.clone());
+        tokio::spawn(async move {
+            pin_mut!(future, closed);
+            select(future, closed).await;
+        });
+    }
+}
+
+async fn wait_closed(mut closed: watch::Receiver<bool>) {
+    while !*closed.borrow() {
+        if closed.changed().await.is_err() {
+            // The server has finished, so it can no longer be closed.
+            pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt, panic, sync::Arc};
+
+use tokio::{sync::Notify, task::JoinHandle};
+
+use super::{track::Tracker, Result};
+
+/// A handle to a [`Server`](crate::Server) running in the background, created by
+/// [`Server::start()`](crate::Server::start).
+///
+/// Dropping the handle does not stop the server.
+///
+/// # Example
+///
+/// ```
+/// use myth::Filter;
+///
+/// # #[tokio::main] async fn main() -> myth::server::Result {
+/// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+/// let handle = myth::serve(filter).bind(([127, 0, 0, 1], 0)).start();
+/// println!("Listening on {}", handle.local_addr());
+///
+/// handle.shutdown();
+/// handle.wait().await?;
+/// # Ok(()) }
+/// ```
+pub struct ServerHandle<A> {
+    pub(crate) local_addr: A,
+    pub(crate) tracker: Arc<Tracker>,
+    pub(crate) shutdown: Arc<Notify>,
+    pub(crate) task: JoinHandle<Result>,
+}
+
+impl<A> ServerHandle<A>
+where
+    A: Clone,
+{
+    /// Returns the local address that the server is bound to.
+    pub fn local_addr(&self) -> A {
+        self.local_addr.clone()
+    }
+}
+
+impl<A> ServerHandle<A> {
+    /// Returns the number of connections that are currently open.
+    pub fn connections(&self) -> usize {
+        self.tracker.connections()
+    }
+
+    /// Returns the number of requests that are currently being handled.
+    pub fn requests(&self) -> usize {
+        self.tracker.requests()
+    }
+
+    /// Starts shutting down the server gracefully.
+    ///
+    /// The server stops accepting new connections, and waits for open connections to finish,
+    /// up to the [`shutdown_timeout`](crate::Server::shutdown_timeout).
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /// Waits for the server to finish running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an error occurred while running the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server panicked.
+    pub async fn wait(self) -> Result {
+        match self.task.await {
+            Ok(result) => result,
+            Err(error) => panic::resume_unwind(error.into_panic()),
+        }
+    }
+}
+
+impl<A> fmt::Debug for ServerHandle<A>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerHandle")
+            .field("local_addr", &self.local_addr)
+            .field("connections", &self.connections())
+            .field("requests", &self.requests())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    convert::Infallible,
+    future::{pending, poll_fn},
+    sync::Arc,
+};
+
+use futures_util::{
+    future::{select, Either},
+    pin_mut,
+};
+use h3::server::{RequestResolver, RequestStream};
+use h3_quinn::{RecvStream, SendStream};
+use hyper::{
+    body::{Buf, HttpBody, Sender},
+    header::{self, HeaderName},
+    rt::Executor,
+    service::Service,
+    Method,
+};
+use tokio::sync::{mpsc, watch};
+
+use super::{exec::Exec, signaled, track::Tracker};
+use crate::{
+    request::{Connection, HyperRequest},
+    service::handle_connection,
+    version::Version,
+    Body, Bytes, Filter, FilterBase, PeerAddr, Responder, Response,
+};
+
+/// Serves HTTP/3 on `endpoint` until `shutdown` is signaled and the open connections have
+/// finished.
+pub(crate) async fn serve<F, R>(
+    endpoint: quinn::Endpoint,
+    filter: Arc<F>,
+    tracker: Arc<Tracker>,
+    exec: Exec,
+    shutdown: watch::Receiver<bool>,
+) where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    let local_addr = endpoint.local_addr().ok().map(PeerAddr::Udp);
+    // Every connection holds a sender, so that receiving fails once they have all finished.
+    let (open, mut finished) = mpsc::channel::<()>(1);
+
+    loop {
+        let accept = endpoint.accept();
+        let signal = signaled(shutdown.clone());
+        pin_mut!(accept, signal);
+        let incoming = match select(accept, signal).await {
+            Either::Left((Some(incoming), _)) => incoming,
+            Either::Left((None, _)) | Either::Right(_) => break,
+        };
+        exec.execute(serve_connection(
+            incoming,
+            local_addr.clone(),
+            Arc::clone(&filter),
+            Arc::clone(&tracker),
+            exec.clone(),
+            shutdown.clone(),
+            open.clone(),
+        ));
+    }
+
+    // Refuse new connections while the open ones finish.
+    endpoint.set_server_config(None);
+    drop(open);
+    let _ = finished.recv().await;
+    endpoint.wait_idle().await;
+}
+
+async fn serve_connection<F, R>(
+    incoming: quinn::Incoming,
+    local_addr: Option<PeerAddr>,
+    filter: Arc<F>,
+    tracker: Arc<Tracker>,
+    exec: Exec,
+    shutdown: watch::Receiver<bool>,
+    open: mpsc::Sender<()>,
+) where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    let connection = match incoming.await {
+        Ok(connection) => connection,
+        Err(error) => {
+            tracing::debug!("Failed to accept QUIC connection: {}", error);
+            return;
+        }
+    };
+    let service = tracker.track(handle_connection(
+        filter,
+        Connection::from_quic(&connection, local_addr),
+        None,
+    ));
+    let mut connection =
+        match h3::server::Connection::new(h3_quinn::Connection::new(connection)).await {
+            Ok(connection) => connection,
+            Err(error) => {
+                tracing::debug!("Failed to start HTTP/3 connection: {}", error);
+                return;
+            }
+        };
+
+    let mut shutting_down = false;
+    loop {
+        let accepted = {
+            let accept = connection.accept();
+            pin_mut!(accept);
+            if shutting_down {
+                Some(accept.await)
+            } else {
+                let signal = signaled(shutdown.clone());
+                pin_mut!(signal);
+                match select(accept, signal).await {
+                    Either::Left((accepted, _)) => Some(accepted),
+                    Either::Right(_) => None,
+                }
+            }
+        };
+        let resolver = match accepted {
+            // Send a GOAWAY, and keep accepting until the requests in flight have finished.
+            None => {
+                shutting_down = true;
+                match connection.shutdown(0).await {
+                    Ok(()) => continue,
+                    Err(error) => {
+                        tracing::debug!("Failed to shut down HTTP/3 connection: {}", error);
+                        break;
+                    }
+                }
+            }
+            Some(Ok(Some(resolver))) => resolver,
+            Some(Ok(None)) => break,
+            Some(Err(error)) => {
+                if !error.is_h3_no_error() {
+                    tracing::debug!("Error on HTTP/3 connection: {}", error);
+                }
+                break;
+            }
+        };
+
+        exec.execute(respond(resolver, service.clone(), open.clone()));
+    }
+}
+
+/// Reads a request, forwards its body to the [`Filter`] while it handles the request, and then
+/// sends the response.
+///
+/// The request is read here rather than in the connection's loop, so that a stream that never
+/// sends its headers does not hold up the other requests of its connection.
+async fn respond<S>(
+    resolver: RequestResolver<h3_quinn::Connection, Bytes>,
+    mut service: S,
+    _open: mpsc::Sender<()>,
+) where
+    S: Service<HyperRequest, Response = Response, Error = Infallible>,
+{
+    let (request, stream) = match resolver.resolve_request().await {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            tracing::debug!("Failed to read HTTP/3 request: {}", error);
+            return;
+        }
+    };
+    let head = request.method() == h3_http::Method::HEAD;
+    let (body_sender, body) = Body::channel();
+    let request = match to_hyper(request, body) {
+        Ok(request) => request,
+        Err(error) => {
+            tracing::debug!("Invalid HTTP/3 request: {}", error);
+            return;
+        }
+    };
+    if let Err(never) = poll_fn(|cx| service.poll_ready(cx)).await {
+        match never {}
+    }
+    let response = service.call(request);
+    let (mut send, recv) = stream.split();
+
+    let forward = async {
+        forward_body(recv, body_sender).await;
+        pending::<()>().await;
+    };
+    let send_response = async {
+        let response = match response.await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+        if let Err(error) = send_response(&mut send, response, head).await {
+            tracing::debug!("Failed to send HTTP/3 response: {}", error);
+        }
+    };
+    pin_mut!(forward, send_response);
+    select(send_response, forward).await;
+}
+
+async fn forward_body(mut recv: RequestStream<RecvStream, Bytes>, mut sender: Sender) {
+    loop {
+        match recv.recv_data().await {
+            Ok(Some(mut data)) => {
+                let data = data.copy_to_bytes(data.remaining());
+                if sender.send_data(data).await.is_err() {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(error) => {
+                tracing::debug!("Failed to read HTTP/3 request body: {}", error);
+                sender.abort();
+                return;
+            }
+        }
+    }
+}
+
+async fn send_response(
+    send: &mut RequestStream<SendStream<Bytes>, Bytes>,
+    response: Response,
+    head: bool,
+) -> Result<(), h3::error::StreamError> {
+    let (parts, mut body) = response.into_parts();
+    let mut builder = h3_http::Response::builder().status(parts.status.as_u16());
+    for (name, value) in &parts.headers {
+        if !is_connection_specific(name) {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+    }
+    let response = builder
+        .body(())
+        .expect("a valid response was converted to an invalid response");
+    send.send_response(response).await?;
+
+    if !head {
+        while let Some(data) = body.data().await {
+            match data {
+                Ok(data) => send.send_data(data).await?,
+                Err(error) => {
+                    tracing::debug!("Failed to read response body: {}", error);
+                    send.stop_stream(h3::error::Code::H3_INTERNAL_ERROR);
+                    return Ok(());
+                }
+            }
+        }
+        if let Ok(Some(trailers)) = body.trailers().await {
+            let mut converted = h3_http::HeaderMap::new();
+            for (name, value) in &trailers {
+                if let (Ok(name), Ok(value)) = (
+                    h3_http::HeaderName::from_bytes(name.as_str().as_bytes()),
+                    h3_http::HeaderValue::from_bytes(value.as_bytes()),
+                ) {
+                    converted.append(name, value);
+                }
+            }
+            send.send_trailers(converted).await?;
+        }
+    }
+    send.finish().await
+}
+
+/// Converts an HTTP/3 request to the version of [`http`] used by the rest of the crate.
+fn to_hyper(request: h3_http::Request<()>, body: Body) -> Result<HyperRequest, hyper::http::Error> {
+    let mut builder = hyper::Request::builder()
+        .method(Method::from_bytes(request.method().as_str().as_bytes())?)
+        .uri(request.uri().to_string())
+        .version(Version::HTTP_3);
+    for (name, value) in request.headers() {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+    builder.body(body)
+}
+
+/// Returns whether a header is specific to an HTTP/1 connection, and must not be sent over
+/// HTTP/3.
+fn is_connection_specific(name: &HeaderName) -> bool {
+    name == header::CONNECTION
+        || name == header::TRANSFER_ENCODING
+        || name == header::UPGRADE
+        || name == "keep-alive"
+        || name == "proxy-connection"
+}
@@ -0,0 +1,892 @@
+//! Provides [`Server`], which is used to actually run a
+//! [`Filter`] as an HTTP server
+
+mod config;
+mod exec;
+mod handle;
+#[cfg(feature = "http3")]
+mod http3;
+mod track;
+
+#[cfg(unix)]
+use std::os::unix::io::OwnedFd;
+use std::{
+    convert::Infallible,
+    error::Error as StdError,
+    fmt,
+    future::{pending, ready, Future},
+    io,
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    sync::Arc,
+    time::Duration,
+};
+
+#[cfg(feature = "http3")]
+use futures_util::future::{join, join_all};
+use futures_util::{future::select, pin_mut, FutureExt};
+use hyper::{
+    header::HeaderValue, server::conn::AddrIncoming, service::make_service_fn, Error as HyperError,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{watch, Notify},
+};
+use tracing::Instrument;
+
+use self::{config::Config, exec::Exec, track::Tracker};
+pub use self::{
+    config::{Http1Config, Http2Config},
+    handle::ServerHandle,
+};
+#[cfg(unix)]
+use crate::unix::UnixIncoming;
+use crate::{
+    proxy::{ProxyAcceptor, ProxyConfig},
+    request::Connection,
+    service::{handle_connection, ConnectionLimits, Incoming, Limited, Merge, RequestStream},
+    Filter, FilterBase, Responder,
+};
+
+macro_rules! make_service {
+    ($filter:expr, $tracker:expr, $alt_svc:expr) => {{
+        let filter: Arc<_> = $filter;
+        let tracker: Arc<Tracker> = $tracker;
+        let alt_svc: Option<HeaderValue> = $alt_svc;
+        make_service_fn(move |stream| {
+            let filter = Arc::clone(&filter);
+            let connection = Connection::from_stream(stream);
+            // HTTP/3 is only served with TLS, so it is not advertised to plain HTTP clients.
+            #[cfg(feature = "tls")]
+            let alt_svc = alt_svc.clone().filter(|_| connection.tls.is_some());
+            #[cfg(not(feature = "tls"))]
+            let alt_svc = alt_svc.clone();
+            let request_service = tracker.track(handle_connection(filter, connection, alt_svc));
+            ready(Ok::<_, Infallible>(request_service))
+        })
+    }};
+}
+
+/// A server that uses a [`Filter`] to handle requests.
+///
+/// # Example
+/// ```no_run
+/// use myth::{Filter, Server};
+///
+/// # #[tokio::main] async fn main() {
+/// // Create a `Filter`.
+/// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+///
+/// // Create a new `Server`.
+/// let server = Server::new(filter);
+///
+/// server
+///     // Bind to public TCP port 80.
+///     .bind(([0, 0, 0, 0], 80))
+///     // Run the server forever.
+///     .run()
+///     // Await the `Future`
+///     .await;
+/// # }
+/// ```
+///
+/// # Protocols
+///
+/// By default, a server accepts both HTTP/1 and HTTP/2 on every listener. Over TLS, the client
+/// picks one with ALPN. Without TLS, HTTP/2 clients must connect with prior knowledge
+/// (cleartext HTTP/2, or h2c), which is detected from the connection preface, so HTTP/1 and h2c
+/// clients can share a port. Use [`http1_only()`](Self::http1_only) or
+/// [`http2_only()`](Self::http2_only) to accept only one of them.
+///
+/// Upgrading an HTTP/1 connection to HTTP/2 with `Upgrade: h2c`, which
+/// [RFC 9113](https://www.rfc-editor.org/rfc/rfc9113#section-3.1) deprecates, is not supported.
+/// Such requests are answered over HTTP/1.1 instead, as the upgrade is optional.
+///
+/// With the `http3` feature, [`and_bind_http3()`](Self::and_bind_http3) also serves HTTP/3 over
+/// QUIC, which clients discover through the `Alt-Svc` header.
+#[derive(Debug)]
+pub struct Server<I, F> {
+    incoming: I,
+    filter: F,
+    config: Config,
+}
+
+impl<I, F, R> Server<I, F>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    /// Runs the server until either a Ctrl-C or `SIGTERM` signal is received or an error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an error occured while preparing or running the server.
+    pub async fn run(self) {
+        if let Err(error) = self.try_run().await {
+            panic!("{}", error);
+        }
+    }
+
+    /// Attempts to run the server until either a Ctrl-C or `SIGTERM` signal is received or an
+    /// error occurs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an error occurred while running the server.
+    pub async fn try_run(self) -> Result {
+        self.run_with_graceful_shutdown(shutdown_signal()).await
+    }
+
+    /// Runs the server until `signal` completes, and then shuts down gracefully.
+    ///
+    /// Once `signal` completes, the server stops accepting new connections and waits for open
+    /// connections to finish, up to the [`shutdown_timeout`](Server::shutdown_timeout).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an error occurred while running the server.
+    pub async fn run_with_graceful_shutdown(self, signal: impl Future<Output = ()>) -> Result {
+        self.serve(Arc::default(), signal).await
+    }
+
+    pub async fn run_without_graceful_shutdown(self) -> Result {
+        let addr = &*self.local_addr().to_string();
+        let filter = Arc::new(self.filter);
+        let tracker = Arc::<Tracker>::default();
+        let alt_svc = self.config.alt_svc();
+
+        let server = self
+            .config
+            .builder(self.incoming)
+            .serve(make_service!(
+                Arc::clone(&filter),
+                Arc::clone(&tracker),
+                alt_svc
+            ))
+            .instrument(tracing::info_span!(
+                "Running server without graceful shutdown",
+                addr
+            ));
+
+        #[cfg(feature = "http3")]
+        let (_signal_sender, server) = {
+            let (_, exec) = Exec::new();
+            let (signal_sender, on_signal) = watch::channel(false);
+            let http3 = self.config.http3.into_iter().map(|endpoint| {
+                http3::serve(
+                    endpoint,
+                    Arc::clone(&filter),
+                    Arc::clone(&tracker),
+                    exec.clone(),
+                    on_signal.clone(),
+                )
+            });
+            (
+                signal_sender,
+                join(server, join_all(http3)).map(|(result, _)| result),
+            )
+        };
+
+        server.await.map_err(Error::Running)
+    }
+
+    /// Starts running the server on a background task, returning a [`ServerHandle`] that can be
+    /// used to shut it down.
+    ///
+    /// Unlike [`run()`](Server::run), this does not listen for any signals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn start(self) -> ServerHandle<I::Addr>
+    where
+        I: Send + 'static,
+    {
+        let local_addr = self.local_addr();
+        let tracker = Arc::<Tracker>::default();
+        let shutdown = Arc::new(Notify::new());
+        let signal = {
+            let shutdown = Arc::clone(&shutdown);
+            async move { shutdown.notified().await }
+        };
+        let task = tokio::spawn(self.serve(Arc::clone(&tracker), signal));
+        ServerHandle {
+            local_addr,
+            tracker,
+            shutdown,
+            task,
+        }
+    }
+
+    async fn serve(self, tracker: Arc<Tracker>, signal: impl Future<Output = ()>) -> Result {
+        let addr = &*self.local_addr().to_string();
+        let shutdown_timeout = self.config.shutdown_timeout;
+        let filter = Arc::new(self.filter);
+        let alt_svc = self.config.alt_svc();
+        let (close, exec) = Exec::new();
+        let (signal_sender, on_signal) = watch::channel(false);
+
+        let server = self
+            .config
+            .builder(self.incoming)
+            .executor(exec.clone())
+            .serve(make_service!(
+                Arc::clone(&filter),
+                Arc::clone(&tracker),
+                alt_svc
+            ))
+            .with_graceful_shutdown(signal.map(move |()| {
+                let _ = signal_sender.send(true);
+            }))
+            .instrument(tracing::info_span!("Running server", addr));
+
+        #[cfg(feature = "http3")]
+        let server = {
+            let http3 = self.config.http3.into_iter().map(|endpoint| {
+                http3::serve(
+                    endpoint,
+                    Arc::clone(&filter),
+                    Arc::clone(&tracker),
+                    exec.clone(),
+                    on_signal.clone(),
+                )
+            });
+            join(server, join_all(http3)).map(|(result, _)| result)
+        };
+
+        let deadline = tokio::spawn(async move {
+            if let (true, Some(timeout)) = (signaled(on_signal).await, shutdown_timeout) {
+                tokio::time::sleep(timeout).await;
+                tracing::warn!(
+                    "Closing {} connections after shutdown timeout",
+                    tracker.connections()
+                );
+                let _ = close.send(true);
+            }
+        });
+
+        let result = server.await;
+        deadline.abort();
+        result.map_err(Error::Running)
+    }
+
+    /// Returns the local address that this server is bound to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    /// # use myth::{Filter, Server};
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    /// let server = Server::new(filter).bind(([127, 0, 0, 1], 8080));
+    /// assert_eq!(server.local_addr(), SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080)));
+    /// # }
+    /// ```
+    pub fn local_addr(&self) -> I::Addr {
+        self.incoming.local_addr()
+    }
+
+    /// Reads a [PROXY protocol](crate::proxy) header from the start of every connection
+    /// accepted so far, so that [`remote_addr()`](crate::remote_addr) returns the address of the
+    /// client instead of the address of the load balancer.
+    ///
+    /// When serving HTTPS, call this before [`with_tls()`](Server::with_tls), since load
+    /// balancers send the header before the TLS handshake.
+    pub fn proxy_protocol(self, config: ProxyConfig) -> Server<ProxyAcceptor<I>, F> {
+        Server {
+            incoming: ProxyAcceptor::new(self.incoming, config),
+            filter: self.filter,
+            config: self.config,
+        }
+    }
+
+    /// Limits the connections accepted so far with [`ConnectionLimits`].
+    ///
+    /// After [`proxy_protocol()`](Server::proxy_protocol), the per-IP limit applies to the
+    /// address of the client instead of the address of the load balancer.
+    ///
+    /// [`max_connections`](ConnectionLimits::max_connections) only bounds the number of open
+    /// file descriptors when this is called before [`proxy_protocol()`](Server::proxy_protocol)
+    /// and [`with_tls()`](Server::with_tls), since those accept connections while they read
+    /// the header or perform the handshake. Otherwise, those connections are only bounded by
+    /// [`ProxyConfig::max_pending()`] and
+    /// [`TlsConfig::max_pending_handshakes()`](crate::TlsConfig::max_pending_handshakes).
+    pub fn connection_limits(self, limits: ConnectionLimits) -> Server<Limited<I>, F> {
+        Server {
+            incoming: Limited::new(self.incoming, limits),
+            filter: self.filter,
+            config: self.config,
+        }
+    }
+
+    /// Also accepts connections from `incoming`, which are handled by the same [`Filter`].
+    ///
+    /// All of the [`Incoming`]s are run and shut down together. To serve both HTTP and HTTPS,
+    /// call [`with_tls()`](Server::with_tls) before adding the plain HTTP listeners.
+    pub fn and_incoming<J>(self, incoming: J) -> Server<Merge<I, J>, F> {
+        Server {
+            incoming: Merge::new(self.incoming, incoming),
+            filter: self.filter,
+            config: self.config,
+        }
+    }
+
+    /// Also binds the server to a TCP address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address could not be bound.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::Filter;
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    ///
+    /// myth::serve(filter)
+    ///     // Serve on port 80 for both IPv4 and IPv6.
+    ///     .bind(([0, 0, 0, 0], 80))
+    ///     .and_bind(([0, 0, 0, 0, 0, 0, 0, 0], 80))
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn and_bind(self, addr: impl Into<SocketAddr>) -> Server<Merge<I, AddrIncoming>, F> {
+        match self.try_and_bind(addr) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to also bind the server to a TCP address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address could not be bound.
+    pub fn try_and_bind(
+        self,
+        addr: impl Into<SocketAddr>,
+    ) -> Result<Server<Merge<I, AddrIncoming>, F>> {
+        bind_tcp(addr.into()).map(|incoming| self.and_incoming(incoming))
+    }
+
+    /// Also accepts connections from an existing TCP listener.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the listener could not be used.
+    pub fn and_from_tcp(self, listener: StdTcpListener) -> Server<Merge<I, AddrIncoming>, F> {
+        match self.try_and_from_tcp(listener) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to also accept connections from an existing TCP listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener could not be used, as with
+    /// [`try_from_tcp()`](Server::try_from_tcp).
+    pub fn try_and_from_tcp(
+        self,
+        listener: StdTcpListener,
+    ) -> Result<Server<Merge<I, AddrIncoming>, F>> {
+        adopt_tcp(listener).map(|incoming| self.and_incoming(incoming))
+    }
+
+    /// Also binds the server to a Unix domain socket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the socket could not be bound.
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn and_bind_unix(
+        self,
+        config: impl Into<crate::UnixConfig>,
+    ) -> Server<Merge<I, UnixIncoming>, F> {
+        match self.try_and_bind_unix(config) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to also bind the server to a Unix domain socket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket could not be bound, as with
+    /// [`try_bind_unix()`](Server::try_bind_unix).
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn try_and_bind_unix(
+        self,
+        config: impl Into<crate::UnixConfig>,
+    ) -> Result<Server<Merge<I, UnixIncoming>, F>> {
+        bind_unix(config.into()).map(|incoming| self.and_incoming(incoming))
+    }
+}
+
+/// Waits for a shutdown to be signaled through `receiver`, returning `false` if the sender was
+/// dropped first.
+async fn signaled(mut receiver: watch::Receiver<bool>) -> bool {
+    loop {
+        if *receiver.borrow_and_update() {
+            return true;
+        }
+        if receiver.changed().await.is_err() {
+            return *receiver.borrow();
+        }
+    }
+}
+
+/// Completes once either a Ctrl-C or a `SIGTERM` signal is received.
+async fn shutdown_signal() {
+    let ctrl_c = tokio::signal::ctrl_c().map(|result| {
+        if let Err(error) = result {
+            tracing::error!("Failed to install ctrl-c shutdown signal: {}", error);
+        }
+    });
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(error) => {
+                tracing::error!("Failed to install SIGTERM shutdown signal: {}", error);
+                pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = pending::<()>();
+
+    pin_mut!(ctrl_c, terminate);
+    select(ctrl_c, terminate).await;
+    tracing::info!("Received shutdown signal");
+}
+
+fn bind_tcp(addr: SocketAddr) -> Result<AddrIncoming> {
+    AddrIncoming::bind(&addr)
+        .inspect(|_| tracing::trace!("Bound server to http://{}", addr))
+        .map_err(Error::Bind)
+}
+
+fn adopt_tcp(listener: StdTcpListener) -> Result<AddrIncoming> {
+    listener.set_nonblocking(true).map_err(Error::Listen)?;
+    let listener = TcpListener::from_std(listener).map_err(Error::Listen)?;
+    AddrIncoming::from_listener(listener)
+        .inspect(|incoming| {
+            tracing::trace!("Adopted listener on http://{}", incoming.local_addr());
+        })
+        .map_err(Error::Bind)
+}
+
+#[cfg(unix)]
+fn bind_unix(config: crate::UnixConfig) -> Result<UnixIncoming> {
+    config
+        .bind()
+        .inspect(|incoming| tracing::trace!("Bound server to {}", incoming.local_addr()))
+        .map_err(Error::Listen)
+}
+
+impl<I, F> Server<I, F> {
+    /// Sets the [`Http1Config`] used for HTTP/1 connections.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use myth::{Filter, Http1Config};
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    ///
+    /// myth::serve(filter)
+    ///     .bind(([127, 0, 0, 1], 8080))
+    ///     .http1(Http1Config::new().header_read_timeout(Duration::from_secs(10)))
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn http1(mut self, config: Http1Config) -> Self {
+        self.config.http1 = config;
+        self
+    }
+
+    /// Sets the [`Http2Config`] used for HTTP/2 connections.
+    pub fn http2(mut self, config: Http2Config) -> Self {
+        self.config.http2 = config;
+        self
+    }
+
+    /// Sets how long a graceful shutdown waits for open connections to finish, after which they
+    /// are closed.
+    ///
+    /// By default, a graceful shutdown waits for as long as it takes.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.config.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether `TCP_NODELAY` is set on accepted connections.
+    ///
+    /// This applies to every TCP listener of the server, including ones that are added later or
+    /// wrapped with TLS.
+    ///
+    /// Defaults to `false`.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Sets the duration after which TCP keepalive probes are sent on idle accepted connections,
+    /// or [`None`] to disable keepalive.
+    ///
+    /// This applies to every TCP listener of the server, including ones that are added later or
+    /// wrapped with TLS.
+    ///
+    /// Defaults to [`None`].
+    pub fn tcp_keepalive(mut self, keepalive: Option<Duration>) -> Self {
+        self.config.tcp_keepalive = keepalive;
+        self
+    }
+
+    /// Only accepts HTTP/1 connections.
+    pub fn http1_only(mut self) -> Self {
+        self.config.http1_only();
+        self
+    }
+
+    /// Only accepts HTTP/2 connections.
+    ///
+    /// Without TLS, clients must connect using HTTP/2 with prior knowledge (h2c). By default,
+    /// these clients are accepted alongside HTTP/1 clients; see [Protocols](Self#protocols).
+    pub fn http2_only(mut self) -> Self {
+        self.config.http2_only();
+        self
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Running(HyperError),
+    Bind(HyperError),
+    Listen(io::Error),
+    /// HTTP/3 was requested without a [`TlsConfig`](crate::TlsConfig) whose certificates can be
+    /// used over QUIC.
+    #[cfg(feature = "http3")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "http3")))]
+    Http3Unavailable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Running(error) => write!(f, "error while running server: {}", error),
+            Self::Bind(error) => write!(f, "error binding server: {}", error),
+            Self::Listen(error) => write!(f, "error creating listener: {}", error),
+            #[cfg(feature = "http3")]
+            Self::Http3Unavailable => f.write_str(
+                "HTTP/3 requires a `TlsConfig` with certificates passed to `with_tls()`",
+            ),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(match self {
+            Self::Running(error) | Self::Bind(error) => error,
+            Self::Listen(error) => error,
+            #[cfg(feature = "http3")]
+            Self::Http3Unavailable => return None,
+        })
+    }
+}
+
+impl From<HyperError> for Error {
+    fn from(error: HyperError) -> Self {
+        Self::Running(error)
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+impl<F, R> Server<(), F>
+where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    pub fn new(filter: F) -> Self {
+        Self {
+            incoming: (),
+            filter,
+            config: Config::default(),
+        }
+    }
+
+    pub fn bind(self, addr: impl Into<SocketAddr>) -> Server<AddrIncoming, F> {
+        match self.try_bind(addr) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    pub fn try_bind(self, addr: impl Into<SocketAddr>) -> Result<Server<AddrIncoming, F>> {
+        bind_tcp(addr.into()).map(|incoming| Server {
+            incoming,
+            filter: self.filter,
+            config: self.config,
+        })
+    }
+
+    /// Uses an existing TCP listener, such as one inherited from another process.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the listener could not be used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::TcpListener;
+    /// # use myth::Filter;
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    /// let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    /// let server = myth::serve(filter).from_tcp(listener);
+    /// assert_eq!(server.local_addr(), addr);
+    /// # }
+    /// ```
+    pub fn from_tcp(self, listener: StdTcpListener) -> Server<AddrIncoming, F> {
+        match self.try_from_tcp(listener) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to use an existing TCP listener, such as one inherited from another process.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener could not be made non-blocking, or if its local address
+    /// could not be determined, such as when it is not a TCP socket.
+    pub fn try_from_tcp(self, listener: StdTcpListener) -> Result<Server<AddrIncoming, F>> {
+        adopt_tcp(listener).map(|incoming| Server {
+            incoming,
+            filter: self.filter,
+            config: self.config,
+        })
+    }
+
+    /// Uses an existing TCP listener from its file descriptor, such as one from
+    /// [`listen_fds()`](crate::listen_fds).
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the listener could not be used.
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn from_fd(self, fd: OwnedFd) -> Server<AddrIncoming, F> {
+        self.from_tcp(fd.into())
+    }
+
+    /// Attempts to use an existing TCP listener from its file descriptor, such as one from
+    /// [`listen_fds()`](crate::listen_fds).
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener could not be used, as with
+    /// [`try_from_tcp()`](Server::try_from_tcp).
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn try_from_fd(self, fd: OwnedFd) -> Result<Server<AddrIncoming, F>> {
+        self.try_from_tcp(fd.into())
+    }
+
+    /// Binds the server to a Unix domain socket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the socket could not be bound.
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn bind_unix(self, config: impl Into<crate::UnixConfig>) -> Server<UnixIncoming, F> {
+        match self.try_bind_unix(config) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to bind the server to a Unix domain socket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a stale socket file could not be removed, if the socket could not be
+    /// bound, or if its permissions could not be set.
+    #[cfg(unix)]
+    #[cfg_attr(myth_docs, doc(cfg(unix)))]
+    pub fn try_bind_unix(
+        self,
+        config: impl Into<crate::UnixConfig>,
+    ) -> Result<Server<UnixIncoming, F>> {
+        bind_unix(config.into()).map(|incoming| Server {
+            incoming,
+            filter: self.filter,
+            config: self.config,
+        })
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<I, F, R> Server<I, F>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    #[cfg_attr(myth_docs, doc(cfg(feature = "tls")))]
+    pub fn with_tls(
+        #[allow(unused_mut)] mut self,
+        config: crate::TlsConfig,
+    ) -> Server<crate::tls::TlsAcceptor<I>, F> {
+        #[cfg(feature = "http3")]
+        {
+            self.config.quic = config.quic_config();
+        }
+        Server {
+            incoming: crate::tls::TlsAcceptor::new(self.incoming, config),
+            filter: self.filter,
+            config: self.config,
+        }
+    }
+}
+
+#[cfg(feature = "http3")]
+impl<I, F, R> Server<I, F>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    /// Also serves HTTP/3 over QUIC on a UDP address, with the certificates of the
+    /// [`TlsConfig`](crate::TlsConfig) passed to [`with_tls()`](Server::with_tls).
+    ///
+    /// Responses over TCP advertise the HTTP/3 endpoints with an `Alt-Svc` header, so that
+    /// clients can switch to them. Requests over HTTP/3 go through the same [`Filter`], and have
+    /// a [`Version`](crate::version::Version) of `HTTP/3.0` and a [`PeerAddr::Udp`] remote
+    /// address. Shutting down the server also closes the HTTP/3 connections gracefully.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address could not be bound, or if HTTP/3 is unavailable for the
+    /// [`TlsConfig`](crate::TlsConfig) passed to [`with_tls()`](Server::with_tls), as with
+    /// [`try_and_bind_http3()`](Server::try_and_bind_http3).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::Filter;
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    /// let tls = myth::TlsConfig::read_file("/path/to/certificate.pem", "/path/to/private/key.pem");
+    /// myth::serve(filter)
+    ///     .bind(([0, 0, 0, 0], 443))
+    ///     .with_tls(tls)
+    ///     .and_bind_http3(([0, 0, 0, 0], 443))
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg_attr(myth_docs, doc(cfg(feature = "http3")))]
+    pub fn and_bind_http3(self, addr: impl Into<SocketAddr>) -> Self {
+        match self.try_and_bind_http3(addr) {
+            Ok(server) => server,
+            Err(error) => {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    /// Attempts to also serve HTTP/3 over QUIC on a UDP address.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address could not be bound.
+    ///
+    /// Returns [`Error::Http3Unavailable`] if TLS was not configured with
+    /// [`with_tls()`](Server::with_tls), or if the [`TlsConfig`](crate::TlsConfig) was created
+    /// from a `rustls::ServerConfig` or requires client certificates, whose certificates cannot
+    /// be used over QUIC.
+    #[cfg_attr(myth_docs, doc(cfg(feature = "http3")))]
+    pub fn try_and_bind_http3(mut self, addr: impl Into<SocketAddr>) -> Result<Self> {
+        let quic = self.config.quic.clone().ok_or(Error::Http3Unavailable)?;
+        let addr = addr.into();
+        let endpoint = quinn::Endpoint::server(quic, addr).map_err(Error::Listen)?;
+        tracing::trace!("Bound HTTP/3 server to https://{}", addr);
+        self.config.http3.push(endpoint);
+        Ok(self)
+    }
+
+    /// Returns the local UDP addresses that HTTP/3 is served on.
+    #[cfg_attr(myth_docs, doc(cfg(feature = "http3")))]
+    pub fn http3_local_addrs(&self) -> Vec<SocketAddr> {
+        self.config
+            .http3
+            .iter()
+            .filter_map(|endpoint| endpoint.local_addr().ok())
+            .collect()
+    }
+}
+
+/// Creates a new [`Server`] from a [`Filter`].
+///
+/// This is equivalent to calling [`Server::new()`].
+pub fn serve<F, R>(filter: F) -> Server<(), F>
+where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    Server::new(filter)
+}
@@ -0,0 +1,141 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use hyper::service::Service;
+use pin_project_lite::pin_project;
+
+/// Counts the connections and requests that are currently being handled by a server.
+#[derive(Debug, Default)]
+pub(crate) struct Tracker {
+    connections: AtomicUsize,
+    requests: AtomicUsize,
+}
+
+impl Tracker {
+    pub(crate) fn connections(&self) -> usize {
+        self.connections.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn requests(&self) -> usize {
+        self.requests.load(Ordering::Acquire)
+    }
+
+    /// Wraps the service for a new connection, which is counted until the service and its clones
+    /// are dropped.
+    pub(crate) fn track<S>(self: &Arc<Self>, service: S) -> Tracked<S> {
+        Tracked {
+            service,
+            connection: Arc::new(Guard::new(Arc::clone(self), |tracker| &tracker.connections)),
+        }
+    }
+}
+
+/// Decrements a counter of a [`Tracker`] when dropped.
+#[derive(Debug)]
+struct Guard {
+    tracker: Arc<Tracker>,
+    counter: fn(&Tracker) -> &AtomicUsize,
+}
+
+impl Guard {
+    fn new(tracker: Arc<Tracker>, counter: fn(&Tracker) -> &AtomicUsize) -> Self {
+        counter(&tracker).fetch_add(1, Ordering::AcqRel);
+        Self { tracker, counter }
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        (self.counter)(&self.tracker).fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A connection's service that is counted by a [`Tracker`].
+#[derive(Clone, Debug)]
+pub(crate) struct Tracked<S> {
+    service: S,
+    connection: Arc<Guard>,
+}
+
+impl<S, R> Service<R> for Tracked<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+
+    type Error = S::Error;
+
+    type Future = TrackedFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        TrackedFuture {
+            future: self.service.call(request),
+            _request: Guard::new(Arc::clone(&self.connection.tracker), |tracker| {
+                &tracker.requests
+            }),
+        }
+    }
+}
+
+pin_project! {
+    /// A request's response future that is counted by a [`Tracker`].
+    pub(crate) struct TrackedFuture<F> {
+        #[pin]
+        future: F,
+        _request: Guard,
+    }
+}
+
+impl<F> Future for TrackedFuture<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        self.project().future.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, future::ready, sync::Arc};
+
+    use hyper::{
+        service::{service_fn, Service},
+        Body, Request, Response,
+    };
+
+    use super::Tracker;
+
+    #[test]
+    fn count() {
+        let tracker = Arc::new(Tracker::default());
+        let mut service = tracker.track(service_fn(|_: Request<Body>| {
+            ready(Ok::<_, Infallible>(Response::new(Body::empty())))
+        }));
+        assert_eq!(tracker.connections(), 1);
+
+        let first = service.call(Request::new(Body::empty()));
+        let second = service.call(Request::new(Body::empty()));
+        assert_eq!(tracker.requests(), 2);
+        drop(first);
+        assert_eq!(tracker.requests(), 1);
+
+        drop(service);
+        assert_eq!(tracker.connections(), 0);
+        drop(second);
+        assert_eq!(tracker.requests(), 0);
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    io,
+    io::IoSlice,
+    net::IpAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::task::AtomicWaker;
+use hyper::server::accept::Accept;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::{Incoming, RequestStream};
+use crate::{proxy::ProxyHeader, PeerAddr};
+
+/// Limits on the connections that a [`Server`](crate::Server) accepts, set with
+/// [`Server::connection_limits()`](crate::Server::connection_limits).
+///
+/// # Example
+///
+/// ```no_run
+/// use myth::{ConnectionLimits, Filter};
+///
+/// # #[tokio::main] async fn main() {
+/// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+///
+/// let limits = ConnectionLimits::new()
+///     .max_connections(10_000)
+///     .max_connections_per_ip(100);
+/// let metrics = limits.metrics();
+///
+/// myth::serve(filter)
+///     .bind(([0, 0, 0, 0], 8080))
+///     .connection_limits(limits)
+///     .run()
+///     .await;
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionLimits {
+    max_connections: Option<usize>,
+    max_connections_per_ip: Option<usize>,
+    metrics: ConnectionMetrics,
+}
+
+impl ConnectionLimits {
+    /// Creates a new configuration without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of connections that are open at once.
+    ///
+    /// Once this is reached, no more connections are accepted until one of them closes. New
+    /// connections wait in the backlog of the listener.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is `0`.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        assert!(max > 0, "the maximum number of connections must not be 0");
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of connections that are open at once from each IP address.
+    ///
+    /// Connections over this limit are closed as soon as they are accepted. Connections without
+    /// an IP address, such as those over Unix domain sockets, are not limited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is `0`.
+    pub fn max_connections_per_ip(mut self, max: usize) -> Self {
+        assert!(max > 0, "the maximum number of connections must not be 0");
+        self.max_connections_per_ip = Some(max);
+        self
+    }
+
+    /// Returns the [`ConnectionMetrics`] of the connections accepted with these limits.
+    pub fn metrics(&self) -> ConnectionMetrics {
+        self.metrics.clone()
+    }
+}
+
+/// Counts of the connections accepted by a [`Server`](crate::Server) with
+/// [`ConnectionLimits`], created by [`ConnectionLimits::metrics()`].
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionMetrics {
+    state: Arc<State>,
+}
+
+impl ConnectionMetrics {
+    /// Returns the total number of connections that have been accepted.
+    pub fn accepted(&self) -> u64 {
+        self.state.accepted.load(Ordering::Acquire)
+    }
+
+    /// Returns the total number of connections that have been closed for exceeding
+    /// [`max_connections_per_ip`](ConnectionLimits::max_connections_per_ip).
+    pub fn rejected(&self) -> u64 {
+        self.state.rejected.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of connections that are currently open.
+    pub fn active(&self) -> usize {
+        self.state.active.load(Ordering::Acquire)
+    }
+}
+
+/// The state shared between a [`Limited`], its connections, and its [`ConnectionMetrics`].
+#[derive(Debug, Default)]
+struct State {
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    active: AtomicUsize,
+    per_ip: Mutex<HashMap<IpAddr, usize>>,
+    /// The wakers of every [`Limited`] sharing this state, which are woken when a connection
+    /// closes to resume accepting.
+    listeners: Mutex<Vec<Weak<AtomicWaker>>>,
+}
+
+impl State {
+    fn wake_listeners(&self) {
+        self.listeners
+            .lock()
+            .unwrap()
+            .retain(|listener| match listener.upgrade() {
+                Some(listener) => {
+                    listener.wake();
+                    true
+                }
+                None => false,
+            });
+    }
+}
+
+pin_project! {
+    /// An [`Incoming`] that limits the connections accepted from another [`Incoming`], created
+    /// by [`Server::connection_limits()`](crate::Server::connection_limits).
+    #[derive(Debug)]
+    pub struct Limited<I> {
+        #[pin]
+        incoming: I,
+        limits: ConnectionLimits,
+        // Each `Limited` has its own waker, since clones of the same `ConnectionLimits` can be
+        // used by several of them.
+        closed: Arc<AtomicWaker>,
+    }
+}
+
+impl<I> Limited<I> {
+    pub(crate) fn new(incoming: I, limits: ConnectionLimits) -> Self {
+        let closed = Arc::new(AtomicWaker::new());
+        limits
+            .metrics
+            .state
+            .listeners
+            .lock()
+            .unwrap()
+            .push(Arc::downgrade(&closed));
+        Self {
+            incoming,
+            limits,
+            closed,
+        }
+    }
+}
+
+impl<I> Incoming for Limited<I>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+{
+    type Addr = I::Addr;
+
+    fn local_addr(&self) -> Self::Addr {
+        self.incoming.local_addr()
+    }
+
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        self.incoming.set_tcp_options(nodelay, keepalive);
+    }
+}
+
+impl<I> Accept for Limited<I>
+where
+    I: Accept,
+    I::Conn: RequestStream,
+{
+    type Conn = LimitedStream<I::Conn>;
+
+    type Error = I::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let mut this = self.project();
+        let state = &this.limits.metrics.state;
+
+        loop {
+            if let Some(max) = this.limits.max_connections {
+                if state.active.load(Ordering::Acquire) >= max {
+                    this.closed.register(cx.waker());
+                    // A connection may have closed before the waker was registered.
+                    if state.active.load(Ordering::Acquire) >= max {
+                        return Poll::Pending;
+                    }
+                }
+            }
+
+            let stream = match this.incoming.as_mut().poll_accept(cx) {
+                Poll::Ready(Some(Ok(stream))) => stream,
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let ip = stream.remote_addr().ip();
+            if let (Some(ip), Some(max)) = (ip, this.limits.max_connections_per_ip) {
+                let mut per_ip = state.per_ip.lock().unwrap();
+                let count = per_ip.entry(ip).or_insert(0);
+                if *count >= max {
+                    drop(per_ip);
+                    state.rejected.fetch_add(1, Ordering::AcqRel);
+                    tracing::debug!("Rejected connection from {} over the per-IP limit", ip);
+                    continue;
+                }
+                *count += 1;
+            }
+
+            state.accepted.fetch_add(1, Ordering::AcqRel);
+            state.active.fetch_add(1, Ordering::AcqRel);
+            let permit = Permit {
+                state: Arc::clone(state),
+                ip: ip.filter(|_| this.limits.max_connections_per_ip.is_some()),
+            };
+            return Poll::Ready(Some(Ok(LimitedStream {
+                inner: stream,
+                _permit: permit,
+            })));
+        }
+    }
+}
+
+/// Releases the place of a connection in a [`Limited`] when dropped.
+#[derive(Debug)]
+struct Permit {
+    state: Arc<State>,
+    /// The address counted towards the per-IP limit.
+    ip: Option<IpAddr>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(ip) = self.ip {
+            let mut per_ip = self.state.per_ip.lock().unwrap();
+            if let Some(count) = per_ip.get_mut(&ip) {
+                *count -= 1;
+                if *count == 0 {
+                    per_ip.remove(&ip);
+                }
+            }
+        }
+        self.state.active.fetch_sub(1, Ordering::AcqRel);
+        self.state.wake_listeners();
+    }
+}
+
+/// A connection accepted by a [`Limited`].
+#[derive(Debug)]
+pub struct LimitedStream<S> {
+    inner: S,
+    _permit: Permit,
+}
+
+impl<S> RequestStream for LimitedStream<S>
+where
+    S: RequestStream,
+{
+    fn remote_addr(&self) -> PeerAddr {
+        self.inner.remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        self.inner.local_addr()
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.inner.proxy_header()
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls_info(&self) -> Option<Arc<crate::tls::TlsInfo>> {
+        self.inner.tls_info()
+    }
+}
+
+impl<S> AsyncRead for LimitedStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for LimitedStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    error::Error as StdError,
+    fmt, io,
+    io::IoSlice,
+    net::SocketAddr,
+    ops::Deref,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use hyper::server::accept::Accept;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::{Incoming, RequestStream};
+use crate::{proxy::ProxyHeader, PeerAddr};
+
+pin_project! {
+    /// An [`Incoming`] that accepts connections from two other [`Incoming`]s, created by
+    /// [`Server::and_incoming()`](crate::Server::and_incoming).
+    ///
+    /// Connections are accepted from both in turn. This ends once both have ended.
+    #[derive(Debug)]
+    pub struct Merge<A, B> {
+        #[pin]
+        first: A,
+        #[pin]
+        second: B,
+        first_done: bool,
+        second_done: bool,
+        poll_second: bool,
+    }
+}
+
+impl<A, B> Merge<A, B> {
+    pub(crate) fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            first_done: false,
+            second_done: false,
+            poll_second: false,
+        }
+    }
+}
+
+impl<A, B> Incoming for Merge<A, B>
+where
+    A: Incoming,
+    A::Conn: RequestStream,
+    A::Error: StdError + Send + Sync + 'static,
+    A::Addr: Into<LocalAddrs>,
+    B: Incoming,
+    B::Conn: RequestStream,
+    B::Error: StdError + Send + Sync + 'static,
+    B::Addr: Into<LocalAddrs>,
+{
+    type Addr = LocalAddrs;
+
+    fn local_addr(&self) -> LocalAddrs {
+        let mut addrs = self.first.local_addr().into();
+        addrs.0.extend(self.second.local_addr().into().0);
+        addrs
+    }
+
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        self.first.set_tcp_options(nodelay, keepalive);
+        self.second.set_tcp_options(nodelay, keepalive);
+    }
+}
+
+impl<A, B> Accept for Merge<A, B>
+where
+    A: Accept,
+    A::Error: StdError + Send + Sync + 'static,
+    B: Accept,
+    B::Error: StdError + Send + Sync + 'static,
+{
+    type Conn = MergeStream<A::Conn, B::Conn>;
+
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let mut this = self.project();
+        *this.poll_second = !*this.poll_second;
+
+        for second in [*this.poll_second, !*this.poll_second] {
+            let poll = if second {
+                if *this.second_done {
+                    continue;
+                }
+                this.second.as_mut().poll_accept(cx).map(|option| {
+                    option.map(|result| result.map(MergeStream::Second).map_err(io::Error::other))
+                })
+            } else {
+                if *this.first_done {
+                    continue;
+                }
+                this.first.as_mut().poll_accept(cx).map(|option| {
+                    option.map(|result| result.map(MergeStream::First).map_err(io::Error::other))
+                })
+            };
+            match poll {
+                Poll::Ready(Some(result)) => return Poll::Ready(Some(result)),
+                Poll::Ready(None) if second => *this.second_done = true,
+                Poll::Ready(None) => *this.first_done = true,
+                Poll::Pending => {}
+            }
+        }
+
+        if *this.first_done && *this.second_done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A connection accepted by a [`Merge`].
+#[derive(Debug)]
+pub enum MergeStream<A, B> {
+    /// A connection from the first [`Incoming`].
+    First(A),
+
+    /// A connection from the second [`Incoming`].
+    Second(B),
+}
+
+impl<A, B> RequestStream for MergeStream<A, B>
+where
+    A: RequestStream,
+    B: RequestStream,
+{
+    fn remote_addr(&self) -> PeerAddr {
+        match self {
+            Self::First(stream) => stream.remote_addr(),
+            Self::Second(stream) => stream.remote_addr(),
+        }
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        match self {
+            Self::First(stream) => stream.local_addr(),
+            Self::Second(stream) => stream.local_addr(),
+        }
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        match self {
+            Self::First(stream) => stream.proxy_header(),
+            Self::Second(stream) => stream.proxy_header(),
+        }
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls_info(&self) -> Option<Arc<crate::tls::TlsInfo>> {
+        match self {
+            Self::First(stream) => stream.tls_info(),
+            Self::Second(stream) => stream.tls_info(),
+        }
+    }
+}
+
+impl<A, B> AsyncRead for MergeStream<A, B>
+where
+    A: AsyncRead + Unpin,
+    B: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::First(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Second(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<A, B> AsyncWrite for MergeStream<A, B>
+where
+    A: AsyncWrite + Unpin,
+    B: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::First(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Second(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::First(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Second(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::First(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Second(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::First(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            Self::Second(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::First(stream) => stream.is_write_vectored(),
+            Self::Second(stream) => stream.is_write_vectored(),
+        }
+    }
+}
+
+/// The local addresses of a [`Merge`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalAddrs(Vec<PeerAddr>);
+
+impl Deref for LocalAddrs {
+    type Target = [PeerAddr];
+
+    fn deref(&self) -> &[PeerAddr] {
+        &self.0
+    }
+}
+
+impl fmt::Display for LocalAddrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, addr) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            addr.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<PeerAddr> for LocalAddrs {
+    fn from(addr: PeerAddr) -> Self {
+        Self(vec![addr])
+    }
+}
+
+impl From<SocketAddr> for LocalAddrs {
+    fn from(addr: SocketAddr) -> Self {
+        Self(vec![addr.into()])
+    }
+}
+
+impl IntoIterator for LocalAddrs {
+    type Item = PeerAddr;
+    type IntoIter = std::vec::IntoIter<PeerAddr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, pin::Pin};
+
+    use hyper::server::{accept::Accept, conn::AddrIncoming};
+    use tokio::net::TcpStream;
+
+    use super::{LocalAddrs, Merge, MergeStream};
+    use crate::{service::Incoming, PeerAddr};
+
+    #[test]
+    fn display_local_addrs() {
+        let mut addrs = LocalAddrs::from(PeerAddr::from(([127, 0, 0, 1], 80)));
+        addrs.0.push(PeerAddr::Unix(Some("/run/myth.sock".into())));
+        assert_eq!(addrs.to_string(), "127.0.0.1:80, unix:/run/myth.sock");
+        assert_eq!(addrs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_tcp_options() {
+        let first = AddrIncoming::bind(&([127, 0, 0, 1], 0).into()).unwrap();
+        let second = AddrIncoming::bind(&([127, 0, 0, 1], 0).into()).unwrap();
+        let addr = second.local_addr();
+        let mut merge = Merge::new(first, second);
+        merge.set_tcp_options(true, None);
+
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let stream = poll_fn(|cx| Pin::new(&mut merge).poll_accept(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        match stream {
+            MergeStream::Second(stream) => assert!(stream.into_inner().nodelay().unwrap()),
+            MergeStream::First(_) => panic!("accepted from the wrong listener"),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+mod limit;
+mod merge;
+#[cfg(feature = "tower")]
+pub(crate) mod tower;
+
+use std::{
+    convert::Infallible, error::Error as StdError, fmt, future::Future, net::SocketAddr, sync::Arc,
+    time::Duration,
+};
+
+use futures_util::Stream;
+use hyper::{
+    header::{HeaderValue, ALT_SVC},
+    server::{
+        accept::Accept,
+        conn::{AddrIncoming, AddrStream},
+    },
+    service::{service_fn, Service},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::Instrument;
+
+#[cfg(feature = "tower")]
+pub use self::tower::{from_service, FilterService, ServiceError};
+pub use self::{
+    limit::{ConnectionLimits, ConnectionMetrics, Limited, LimitedStream},
+    merge::{LocalAddrs, Merge, MergeStream},
+};
+#[cfg(feature = "tower")]
+pub use crate::filter::Layered;
+#[cfg(unix)]
+pub use crate::unix::{UnixConnection, UnixIncoming};
+use crate::{
+    outcome::Outcome,
+    proxy::ProxyHeader,
+    request::{self, Connection, HyperRequest},
+    Filter, FilterBase, PeerAddr, Responder, Response,
+};
+
+/// An incoming stream of connections that can be used by a [`Server`](crate::Server).
+///
+/// This should be able to [`Accept`] incoming requests and produce a [`RequestStream`].
+pub trait Incoming: Accept
+where
+    <Self as Accept>::Conn: RequestStream,
+    <Self as Accept>::Error: StdError + Send + Sync + 'static,
+{
+    /// The type of address that this incoming stream is bound to.
+    type Addr: Clone + fmt::Debug + fmt::Display + Send + Sync + 'static;
+
+    /// Returns the address that this incoming stream is bound to.
+    fn local_addr(&self) -> Self::Addr;
+
+    /// Sets the [`tcp_nodelay`](crate::Server::tcp_nodelay) and
+    /// [`tcp_keepalive`](crate::Server::tcp_keepalive) options of a [`Server`](crate::Server) on
+    /// the TCP listeners of this incoming stream.
+    ///
+    /// This does nothing by default. Incoming streams that wrap another should pass the options
+    /// on to it.
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        let _ = (nodelay, keepalive);
+    }
+}
+
+/// A stream of requests produced by an [`Incoming`] that can be read by a [`Server`](crate::Server).
+pub trait RequestStream: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+    /// Returns the remote address of the client.
+    fn remote_addr(&self) -> PeerAddr;
+
+    /// Returns the local address that the client connected to, if it is known.
+    fn local_addr(&self) -> Option<PeerAddr> {
+        None
+    }
+
+    /// Returns the PROXY protocol header that was read from the connection, if any.
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        None
+    }
+
+    /// Returns information about the TLS session of the connection, if it uses TLS.
+    #[cfg(feature = "tls")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "tls")))]
+    fn tls_info(&self) -> Option<Arc<crate::tls::TlsInfo>> {
+        None
+    }
+}
+
+impl Incoming for AddrIncoming {
+    type Addr = SocketAddr;
+
+    fn local_addr(&self) -> SocketAddr {
+        Self::local_addr(self)
+    }
+
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        self.set_nodelay(nodelay).set_keepalive(keepalive);
+    }
+}
+
+impl RequestStream for AddrStream {
+    fn remote_addr(&self) -> PeerAddr {
+        PeerAddr::Tcp(Self::remote_addr(self))
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        Some(PeerAddr::Tcp(Self::local_addr(self)))
+    }
+}
+
+/// Creates a [`Service`] to handle requests, given a [`Filter`] and the [`remote_addr`](crate::remote_addr)
+/// of the request.
+///
+/// # Example
+/// ```no_run
+/// use std::{net::SocketAddr, sync::Arc};
+///
+/// use myth::Filter;
+/// use tower::make;
+///
+/// # #[tokio::main] async fn main() -> hyper::Result<()> {
+/// let filter = myth::any().handle(|| async { Ok("Hello from a service!") });
+/// // Wrap our `filter` with an `Arc`. This is necessary for `Filter`s that cannot be cloned.
+/// let filter = Arc::new(filter);
+/// // Create a service with a placeholder `remote_addr` of `0.0.0.0:0`
+/// let service = myth::service::handle_requests(filter, SocketAddr::from(([0, 0, 0, 0], 0)));
+/// // Run the service on a `hyper` server.
+/// hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 3000)))
+///     .serve(make::Shared::new(service))
+///     .await?;
+/// # Ok(()) }
+/// ```
+pub fn handle_requests<F, R>(
+    filter_wrap: impl AsRef<F> + Clone + Send + 'static,
+    remote_addr: impl Into<PeerAddr>,
+) -> impl Service<
+    HyperRequest,
+    Response = Response,
+    Error = Infallible,
+    Future = impl Future<Output = Result<Response, Infallible>> + Send,
+> + Clone
+       + Send
+       + 'static
+where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    handle_connection(filter_wrap, Connection::new(remote_addr.into()), None)
+}
+
+/// Creates a [`Service`] to handle the requests of a connection, adding `alt_svc` to responses
+/// that do not already have an `Alt-Svc` header.
+pub(crate) fn handle_connection<F, R>(
+    filter_wrap: impl AsRef<F> + Clone + Send + 'static,
+    connection: Connection,
+    alt_svc: Option<HeaderValue>,
+) -> impl Service<
+    HyperRequest,
+    Response = Response,
+    Error = Infallible,
+    Future = impl Future<Output = Result<Response, Infallible>> + Send,
+> + Clone
+       + Send
+       + 'static
+where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    service_fn(move |request: HyperRequest| {
+        let filter_wrap = filter_wrap.clone();
+        let (request, request_state) = request::from_hyper(request, connection.clone());
+        let alt_svc = alt_svc.clone();
+
+        async move {
+            let span = tracing::trace_span!(
+                "Incoming request",
+                method = %request.method,
+                uri = %request.uri,
+                remote_addr = %request.connection.remote_addr,
+            );
+            let filter = filter_wrap.as_ref();
+            let future = filter.execute(&request, request_state, ()).instrument(span);
+            let mut response = match future.await.outcome {
+                Outcome::Success((responder,)) => responder.into_response(),
+                Outcome::Error(error) => error.into_response(),
+                Outcome::Forward { forwarding, .. } => forwarding.into_response(),
+            };
+            if let Some(alt_svc) = alt_svc {
+                response.headers_mut().entry(ALT_SVC).or_insert(alt_svc);
+            }
+            Ok::<_, Infallible>(response)
+        }
+    })
+}
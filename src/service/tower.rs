@@ -0,0 +1,427 @@
+use std::{
+    any::Any,
+    convert::Infallible,
+    error::Error as StdError,
+    fmt,
+    future::{poll_fn, Future},
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::stream;
+use hyper::body::{Buf, HttpBody};
+use tower_service::Service;
+
+use crate::{
+    errors::BoxedFilterError,
+    filter::{FilterExecute, FilterSealed},
+    impl_Filter,
+    outcome::{Outcome, RequestOutcome},
+    request::{self, Connection, HyperRequest, Mounted, Request, RequestState},
+    uri::Uri,
+    Body, Filter, FilterBase, Forwarding, PeerAddr, Responder, Response,
+};
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// A [`Service`] that handles requests with a [`Filter`], created by
+/// [`Filter::into_service()`].
+///
+/// Unlike [`handle_requests()`](super::handle_requests), the service does not know the address
+/// of the client, so [`remote_addr()`](crate::remote_addr) extracts the unspecified address
+/// `0.0.0.0:0`.
+#[cfg_attr(myth_docs, doc(cfg(feature = "tower")))]
+pub struct FilterService<F> {
+    filter: Arc<F>,
+}
+
+impl<F> FilterService<F> {
+    pub(crate) fn new(filter: F) -> Self {
+        Self {
+            filter: Arc::new(filter),
+        }
+    }
+}
+
+impl<F> Clone for FilterService<F> {
+    fn clone(&self) -> Self {
+        Self {
+            filter: Arc::clone(&self.filter),
+        }
+    }
+}
+
+impl<F> fmt::Debug for FilterService<F>
+where
+    F: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterService")
+            .field("filter", &self.filter)
+            .finish()
+    }
+}
+
+impl<F, R> Service<HyperRequest> for FilterService<F>
+where
+    F: Filter + for<'f> FilterBase<'f, Input = (), Success = (R,)>,
+    R: Responder + 'static,
+{
+    type Response = Response;
+
+    type Error = Infallible;
+
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HyperRequest) -> Self::Future {
+        let filter = Arc::clone(&self.filter);
+        let mounted = request.extensions().get::<Mounted>().is_some();
+        let connection = Connection::new(PeerAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], 0))));
+        let (request, request_state) = request::from_hyper(request, connection);
+
+        Box::pin(async move {
+            let RequestOutcome {
+                request_state,
+                outcome,
+            } = filter.execute(&request, request_state, ()).await;
+            Ok(match outcome {
+                Outcome::Success((responder,)) => responder.into_response(),
+                Outcome::Error(error) => error.into_response(),
+                // Let the `Filter::layer()` that mounted the request forward it.
+                Outcome::Forward { forwarding, .. } if mounted => {
+                    let mut response = forwarding.clone().into_response();
+                    response.extensions_mut().insert(Forwarded {
+                        forwarding,
+                        request_state,
+                    });
+                    response
+                }
+                Outcome::Forward { forwarding, .. } => forwarding.into_response(),
+            })
+        })
+    }
+}
+
+/// A [`Filter`] that forwarded a request mounted by [`Filter::layer()`], returned with the
+/// request state so that other [`Filter`]s can still read the body.
+#[derive(Debug)]
+pub(crate) struct Forwarded {
+    pub(crate) forwarding: Forwarding,
+    pub(crate) request_state: RequestState,
+}
+
+/// An error returned by a [`Service`] that is used with [`from_service()`] or
+/// [`Filter::layer()`], such as a timeout.
+///
+/// Unless it is recovered, it results in a `500 Internal Server Error`.
+#[cfg_attr(myth_docs, doc(cfg(feature = "tower")))]
+#[derive(Debug)]
+pub struct ServiceError {
+    inner: BoxError,
+}
+
+impl ServiceError {
+    fn new(error: impl Into<BoxError>) -> Self {
+        Self {
+            inner: error.into(),
+        }
+    }
+
+    /// Returns the error of the [`Service`], which can be downcast to a specific type.
+    #[must_use]
+    pub fn into_inner(self) -> BoxError {
+        self.inner
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error from service: {}", self.inner)
+    }
+}
+
+impl StdError for ServiceError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.inner)
+    }
+}
+
+/// Creates a request for a [`Service`] with `uri`, moving the body and upgrade of the request
+/// into it.
+pub(crate) fn into_hyper(
+    request: &Request,
+    mut request_state: RequestState,
+    uri: Uri,
+) -> (HyperRequest, RequestState) {
+    let (body, on_upgrade) = request_state.take_body();
+    let mut hyper_request = HyperRequest::new(body);
+    *hyper_request.method_mut() = request.method.clone();
+    *hyper_request.uri_mut() = uri;
+    *hyper_request.version_mut() = request.version;
+    *hyper_request.headers_mut() = request.headers.clone();
+    if let Some(on_upgrade) = on_upgrade {
+        hyper_request.extensions_mut().insert(on_upgrade);
+    }
+    (hyper_request, request_state)
+}
+
+/// Waits for `service` to be ready, and then calls it with `request`.
+pub(crate) async fn call<S, B>(
+    mut service: S,
+    request: HyperRequest,
+) -> Result<Response, BoxedFilterError>
+where
+    S: Service<HyperRequest, Response = hyper::Response<B>>,
+    S::Error: Into<BoxError>,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    poll_fn(|cx| service.poll_ready(cx))
+        .await
+        .map_err(ServiceError::new)?;
+    let response = service.call(request).await.map_err(ServiceError::new)?;
+    Ok(response.map(into_body))
+}
+
+/// Converts the body of a response from a [`Service`] into a [`Body`].
+fn into_body<B>(body: B) -> Body
+where
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    let mut body = Some(body);
+    if let Some(body) = (&mut body as &mut dyn Any).downcast_mut::<Option<Body>>() {
+        return body.take().expect("the body was just set");
+    }
+    let body = Box::pin(body.expect("the body was just set"));
+    Body::wrap_stream(stream::unfold(body, |mut body| async move {
+        let data = body.data().await?;
+        Some((
+            data.map(|mut data| data.copy_to_bytes(data.remaining())),
+            body,
+        ))
+    }))
+}
+
+/// Creates a [`Filter`] that passes requests to a [`Service`], such as one from the
+/// [Tower](https://docs.rs/tower) ecosystem.
+///
+/// The [`Service`] gets the part of the path that has not been matched by previous
+/// [`Filter`]s, so it can be mounted under a path prefix. The `Host` and other headers, the body,
+/// and [upgrades](crate::upgrade) are passed unchanged.
+///
+/// Errors of the [`Service`] become a [`ServiceError`].
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use myth::{service::from_service, Body, Filter, Response};
+///
+/// // A `Service` that responds with the path that it gets.
+/// let service = tower::service_fn(|request: hyper::Request<Body>| async move {
+///     Ok::<_, Infallible>(Response::new(Body::from(request.uri().path().to_owned())))
+/// });
+///
+/// // Requests to `/legacy/users` get `/users`.
+/// let filter = myth::path::literal("legacy").and(from_service(service));
+/// ```
+#[cfg_attr(myth_docs, doc(cfg(feature = "tower")))]
+pub fn from_service<S, B>(service: S) -> impl_Filter!(Response => Clone + (fmt::Debug))
+where
+    S: Service<HyperRequest, Response = hyper::Response<B>> + Clone + Send + Sync + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    #[derive(Clone)]
+    struct FromService<S>(S);
+
+    impl<S> fmt::Debug for FromService<S> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("FromService").finish_non_exhaustive()
+        }
+    }
+
+    impl<S> FilterSealed for FromService<S> {}
+
+    impl<'f, S> FilterBase<'f> for FromService<S>
+    where
+        S: Send + Sync + 'static,
+    {
+        type Input = ();
+
+        type Success = (Response,);
+    }
+
+    impl<'f, S, B> FilterExecute<'f> for FromService<S>
+    where
+        S: Service<HyperRequest, Response = hyper::Response<B>> + Clone + Send + Sync + 'static,
+        S::Future: Send,
+        S::Error: Into<BoxError>,
+        B: HttpBody + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<BoxError>,
+    {
+        type Future = Pin<Box<dyn Future<Output = RequestOutcome<(), (Response,)>> + Send + 'f>>;
+
+        fn execute(
+            &'f self,
+            request: &'f Request,
+            request_state: RequestState,
+            (): Self::Input,
+        ) -> Self::Future {
+            let uri = unmatched_uri(request, &request_state);
+            let (hyper_request, mut request_state) = into_hyper(request, request_state, uri);
+            request_state.end_current_path_index(request);
+            let service = self.0.clone();
+
+            Box::pin(async move {
+                RequestOutcome {
+                    request_state,
+                    outcome: call(service, hyper_request)
+                        .await
+                        .map(|response| (response,))
+                        .into(),
+                }
+            })
+        }
+    }
+
+    FromService(service)
+}
+
+/// Returns the URI of a request with only the part of the path that has not been matched.
+fn unmatched_uri(request: &Request, request_state: &RequestState) -> Uri {
+    let path = request_state.current_path(request);
+    let slash = if path.starts_with('/') { "" } else { "/" };
+    let path_and_query = match request.uri.query() {
+        Some(query) => format!("{}{}?{}", slash, path, query),
+        None => format!("{}{}", slash, path),
+    };
+    let mut parts = request.uri.clone().into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .expect("part of a valid path is a valid path"),
+    );
+    Uri::from_parts(parts).expect("a valid URI with a valid path is a valid URI")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, fmt, io::Read, time::Duration};
+
+    use ::tower::{service_fn, timeout::TimeoutLayer, util::MapResponseLayer, ServiceExt};
+
+    use super::{from_service, ServiceError};
+    use crate::{
+        body, header::HeaderValue, impl_Filter, path, test, Body, Filter, Forwarding, Response,
+        StatusCode,
+    };
+
+    fn echo_path() -> impl_Filter!(Response => Clone + (fmt::Debug)) {
+        from_service(service_fn(|request: hyper::Request<Body>| async move {
+            let body = match request.uri().query() {
+                Some(query) => format!("{}?{}", request.uri().path(), query),
+                None => request.uri().path().to_owned(),
+            };
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        }))
+    }
+
+    #[tokio::test]
+    async fn into_service() {
+        let filter = path::literal("hello").handle(|| async { Ok("Hello world!") });
+        let service = filter.into_service();
+
+        let request = hyper::Request::get("/hello").body(Body::empty()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(bytes, "Hello world!");
+
+        let request = hyper::Request::get("/other").body(Body::empty()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn layer() {
+        let filter = path::literal("layered")
+            .and(body::all())
+            .handle(|mut body| async move {
+                let mut bytes = Vec::new();
+                Read::read_to_end(&mut body, &mut bytes).unwrap();
+                Ok(bytes)
+            })
+            .layer(MapResponseLayer::new(|mut response: Response| {
+                response
+                    .headers_mut()
+                    .insert("X-Layered", HeaderValue::from_static("1"));
+                response
+            }));
+
+        let response = test::post()
+            .uri("/layered")
+            .body("body")
+            .response(&filter)
+            .await;
+        assert_eq!(response.headers()["X-Layered"], "1");
+        assert_eq!(response.body(), "body");
+
+        // Forwarding passes through the layer, with the body intact.
+        let filter = filter.or(path::literal("other")
+            .and(body::all())
+            .handle(|_| async { Ok(Response::new(Body::from("other"))) }));
+        let response = test::post()
+            .uri("/other")
+            .body("body")
+            .response(&filter)
+            .await;
+        assert!(response.headers().get("X-Layered").is_none());
+        assert_eq!(response.body(), "other");
+        assert!(matches!(
+            test::get().uri("/missing").forwarding(&filter).await,
+            Forwarding::NotFound
+        ));
+    }
+
+    #[tokio::test]
+    async fn layer_error() {
+        let filter = crate::any()
+            .handle(|| async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok("Too late")
+            })
+            .layer(TimeoutLayer::new(Duration::from_millis(10)));
+        let error: ServiceError = test::get().error(&filter).await;
+        assert!(error.into_inner().is::<::tower::timeout::error::Elapsed>());
+    }
+
+    #[tokio::test]
+    async fn mounted() {
+        let filter = path::literal("legacy").and(echo_path());
+        let response = test::get()
+            .uri("/legacy/users?page=2")
+            .response(&filter)
+            .await;
+        assert_eq!(response.body(), "/users?page=2");
+
+        let response = test::get().uri("/legacy").response(&filter).await;
+        assert_eq!(response.body(), "/");
+
+        test::get().uri("/other").not_found(&filter).await;
+    }
+}
@@ -1,17 +1,17 @@
 //! Utilities to test [`Filter`]s.
 
-use std::{convert::TryInto, net::SocketAddr};
+use std::convert::TryInto;
 
 use crate::{
     errors::Recoverable,
     header::{self, HeaderMap, HeaderName, HeaderValue},
     method::Method,
     outcome::{Outcome, RequestOutcome},
-    request::{Request, RequestState},
+    request::{Connection, Request, RequestState},
     traits::{NonEmptyTupleFor, TupleFnOnceFor},
     uri::Uri,
     version::Version,
-    Body, Bytes, Filter, FilterBase, Forwarding, Responder,
+    Body, Bytes, Filter, FilterBase, Forwarding, PeerAddr, Responder,
 };
 
 #[derive(Debug)]
@@ -21,7 +21,7 @@ pub struct RequestBuilder<Input> {
     uri: Uri,
     version: Version,
     headers: HeaderMap,
-    remote_addr: SocketAddr,
+    connection: Connection,
     body: Body,
     input: Input,
 }
@@ -35,7 +35,7 @@ impl RequestBuilder<()> {
             uri: Uri::from_static("/"),
             version: Version::HTTP_11,
             headers: HeaderMap::new(),
-            remote_addr: ([0, 0, 0, 0], 0).into(),
+            connection: Connection::new(PeerAddr::from(([0, 0, 0, 0], 0))),
             body: Body::empty(),
             input: (),
         }
@@ -48,7 +48,7 @@ impl RequestBuilder<()> {
             uri: self.uri,
             version: self.version,
             headers: self.headers,
-            remote_addr: self.remote_addr,
+            connection: self.connection,
             body: self.body,
             input,
         }
@@ -160,8 +160,36 @@ impl<Input> RequestBuilder<Input> {
     /// RequestBuilder::new()
     ///     .remote_addr(([127, 0, 0, 1], 12345));
     /// ```
-    pub fn remote_addr(mut self, addr: impl Into<SocketAddr>) -> Self {
-        self.remote_addr = addr.into();
+    pub fn remote_addr(mut self, addr: impl Into<PeerAddr>) -> Self {
+        self.connection.remote_addr = addr.into();
+        self
+    }
+
+    /// Sets the local address that the request was received on.
+    ///
+    /// # Example
+    /// ```
+    /// # use myth::test::RequestBuilder;
+    /// RequestBuilder::new()
+    ///     .local_addr(([127, 0, 0, 1], 8080));
+    /// ```
+    pub fn local_addr(mut self, addr: impl Into<PeerAddr>) -> Self {
+        self.connection.local_addr = Some(addr.into());
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn proxy_header(
+        mut self,
+        header: std::sync::Arc<crate::proxy::ProxyHeader>,
+    ) -> Self {
+        self.connection.proxy_header = Some(header);
+        self
+    }
+
+    #[cfg(all(test, feature = "tls"))]
+    pub(crate) fn tls(mut self, info: std::sync::Arc<crate::tls::TlsInfo>) -> Self {
+        self.connection.tls = Some(info);
         self
     }
 
@@ -289,13 +317,29 @@ impl<Input> RequestBuilder<Input> {
         }
     }
 
+    /// Gets the [`Forwarding`] of a [`Filter`], panicking if it did not forward.
+    pub async fn forwarding<T>(self, filter: &T) -> Forwarding
+    where
+        T: Filter + for<'f> FilterBase<'f, Input = Input>,
+    {
+        let (request, request_state, input) = self.into_args();
+        let RequestOutcome { outcome, .. } = filter.execute(&request, request_state, input).await;
+        match outcome {
+            Outcome::Success(_) => panic!("Expected forwarding, instead got success"),
+            Outcome::Error(error) => {
+                panic!("Expected forwarding, instead got error {:?}", error)
+            }
+            Outcome::Forward { forwarding, .. } => forwarding,
+        }
+    }
+
     fn into_args(self) -> (Request, RequestState, Input) {
         let Self {
             method,
             uri,
             version,
             headers,
-            remote_addr,
+            connection,
             body,
             input,
         } = self;
@@ -305,7 +349,7 @@ impl<Input> RequestBuilder<Input> {
             uri,
             version,
             headers,
-            remote_addr,
+            connection,
         };
         (request, request_state, input)
     }
@@ -0,0 +1,170 @@
+use std::{
+    convert::TryFrom,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use tokio_rustls::rustls::Certificate;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+/// Whether a [`TlsConfig`](super::TlsConfig) requires clients to present a certificate, set
+/// with [`TlsConfig::client_auth()`](super::TlsConfig::client_auth).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientAuth {
+    /// Clients may connect without a certificate, but a certificate that they present must be
+    /// valid.
+    Optional,
+
+    /// Clients without a valid certificate fail the handshake.
+    Required,
+}
+
+/// A certificate that a client presented and that was verified during the handshake,
+/// extracted by [`peer_certificates()`](super::peer_certificates).
+#[derive(Clone, Debug)]
+pub struct PeerCertificate {
+    cert: Certificate,
+    subject: String,
+    common_name: Option<String>,
+    subject_alt_names: Vec<SubjectAltName>,
+}
+
+impl PeerCertificate {
+    pub(super) fn new(cert: Certificate) -> Self {
+        let mut subject = String::new();
+        let mut common_name = None;
+        let mut subject_alt_names = Vec::new();
+
+        match X509Certificate::from_der(&cert.0) {
+            Ok((_, parsed)) => {
+                subject = parsed.subject().to_string();
+                common_name = parsed
+                    .subject()
+                    .iter_common_name()
+                    .next()
+                    .and_then(|name| name.as_str().ok())
+                    .map(str::to_owned);
+                if let Ok(Some(extension)) = parsed.subject_alternative_name() {
+                    subject_alt_names = extension
+                        .value
+                        .general_names
+                        .iter()
+                        .filter_map(SubjectAltName::from_general_name)
+                        .collect();
+                }
+            }
+            Err(error) => tracing::debug!("Failed to parse peer certificate: {}", error),
+        }
+
+        Self {
+            cert,
+            subject,
+            common_name,
+            subject_alt_names,
+        }
+    }
+
+    /// Returns the certificate as it was sent, in DER.
+    pub fn der(&self) -> &[u8] {
+        &self.cert.0
+    }
+
+    /// Returns the subject of the certificate in the format of
+    /// [RFC 4514](https://datatracker.ietf.org/doc/html/rfc4514), such as
+    /// `CN=client, O=Example`.
+    ///
+    /// This is empty if the subject could not be parsed.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the first common name (`CN`) of the subject.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Returns the subject alternative names of the certificate.
+    pub fn subject_alt_names(&self) -> &[SubjectAltName] {
+        &self.subject_alt_names
+    }
+}
+
+/// A subject alternative name (SAN) of a [`PeerCertificate`].
+///
+/// Names of other kinds are omitted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubjectAltName {
+    /// A DNS name, such as `client.example.com`.
+    Dns(String),
+
+    /// An email address.
+    Email(String),
+
+    /// A URI, such as a SPIFFE ID.
+    Uri(String),
+
+    /// An IP address.
+    Ip(IpAddr),
+}
+
+impl SubjectAltName {
+    fn from_general_name(name: &GeneralName<'_>) -> Option<Self> {
+        match *name {
+            GeneralName::DNSName(name) => Some(Self::Dns(name.to_owned())),
+            GeneralName::RFC822Name(email) => Some(Self::Email(email.to_owned())),
+            GeneralName::URI(uri) => Some(Self::Uri(uri.to_owned())),
+            GeneralName::IPAddress(bytes) => {
+                if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+                    Some(Self::Ip(Ipv4Addr::from(octets).into()))
+                } else if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+                    Some(Self::Ip(Ipv6Addr::from(octets).into()))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_rustls::rustls::Certificate;
+
+    use super::{PeerCertificate, SubjectAltName};
+
+    #[test]
+    fn parse_peer_certificate() {
+        let mut params = rcgen::CertificateParams::new(vec!["client.example.com".to_owned()]);
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "client");
+        params.subject_alt_names.push(rcgen::SanType::URI(
+            "spiffe://example.com/client".to_owned(),
+        ));
+        params
+            .subject_alt_names
+            .push(rcgen::SanType::IpAddress([192, 0, 2, 1].into()));
+        let cert = rcgen::Certificate::from_params(params).unwrap();
+
+        let cert = PeerCertificate::new(Certificate(cert.serialize_der().unwrap()));
+        assert_eq!(cert.subject(), "CN=client");
+        assert_eq!(cert.common_name(), Some("client"));
+        assert_eq!(
+            cert.subject_alt_names(),
+            [
+                SubjectAltName::Dns("client.example.com".to_owned()),
+                SubjectAltName::Uri("spiffe://example.com/client".to_owned()),
+                SubjectAltName::Ip([192, 0, 2, 1].into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unparsable_peer_certificate() {
+        let cert = PeerCertificate::new(Certificate(b"not a certificate".to_vec()));
+        assert_eq!(cert.der(), b"not a certificate");
+        assert_eq!(cert.subject(), "");
+        assert_eq!(cert.common_name(), None);
+        assert!(cert.subject_alt_names().is_empty());
+    }
+}
@@ -0,0 +1,1066 @@
+//! TLS support using [Rustls](rustls).
+//!
+//! [`Server::with_tls()`](crate::Server::with_tls) serves HTTPS with a [`TlsConfig`]. The
+//! [`info()`], [`server_name()`], and [`peer_certificates()`] filters extract information about
+//! the TLS session of a connection.
+
+mod client;
+mod load;
+#[cfg(feature = "http3")]
+mod quic;
+mod reload;
+#[cfg(feature = "self-signed")]
+mod self_signed;
+
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    io::{self, BufRead, IoSlice},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{stream::FuturesUnordered, Stream};
+use hyper::server::accept::Accept;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_rustls::rustls::{
+    self,
+    server::{
+        AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientHello,
+        ResolvesServerCert,
+    },
+    sign::CertifiedKey,
+    Certificate, PrivateKey, RootCertStore,
+};
+
+pub use self::client::{ClientAuth, PeerCertificate, SubjectAltName};
+pub use self::load::Error;
+use self::reload::Reloadable;
+pub use self::reload::TlsReloader;
+#[cfg(feature = "self-signed")]
+pub use self::self_signed::SelfSigned;
+use crate::{
+    filter::ready::ready_filter,
+    impl_Filter,
+    outcome::Outcome,
+    proxy::ProxyHeader,
+    service::{Incoming, RequestStream},
+    PeerAddr,
+};
+
+/// A configuration for [Rustls](rustls) TLS, to be used with
+/// [`Server::with_tls()`](crate::Server::with_tls).
+///
+/// By default, this uses ALPN protocols for `h2` and `http/1.1`. Handshakes must complete
+/// within 10 seconds, and at most 1024 are performed at once.
+///
+/// # Custom Configuration
+///
+/// This can be created from a [`rustls::ServerConfig`] for more fine-grained configuration.
+///
+/// ```
+/// # use myth::TlsConfig;
+/// # use tokio_rustls::rustls;
+/// # fn config() -> Result<(), rustls::Error> {
+/// let cert = rustls::Certificate(b"CERTIFICATE_GOES_HERE".to_vec());
+/// let key = rustls::PrivateKey(b"PRIVATE_KEY_GOES_HERE".to_vec());
+///
+/// let config = rustls::ServerConfig::builder()
+///     .with_safe_defaults()
+///     .with_no_client_auth()
+///     .with_single_cert(vec![cert], key)?;
+///
+/// let config = TlsConfig::from(config);
+/// # Ok(())
+/// # }
+/// # assert!(config().is_err());
+/// ```
+#[cfg_attr(myth_docs, doc(cfg(feature = "tls")))]
+pub struct TlsConfig {
+    pub(crate) config: rustls::ServerConfig,
+    /// The certificates for HTTP/3, which are unknown for a config created from a
+    /// [`rustls::ServerConfig`].
+    #[cfg(feature = "http3")]
+    certs: Option<Arc<Reloadable>>,
+    handshake_timeout: Duration,
+    max_pending_handshakes: usize,
+}
+
+/// The default for [`TlsConfig::handshake_timeout()`].
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The default for [`TlsConfig::max_pending_handshakes()`].
+const MAX_PENDING_HANDSHAKES: usize = 1024;
+
+impl TlsConfig {
+    /// Creates a new TLS config using the provided certificate chain and private key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provided private key was invalid or does not match the first
+    /// certificate of the chain.
+    pub fn try_new(cert_chain: Vec<Certificate>, key: PrivateKey) -> Result<Self, Error> {
+        let resolver = SniResolver {
+            default: Some(load::certified_key(cert_chain, &key)?),
+            ..SniResolver::default()
+        };
+        Ok(Self::with_resolver(Arc::new(Reloadable::new(resolver))))
+    }
+
+    /// Creates a new TLS config using the provided certificate chain and private key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided private key was invalid or does not match the first certificate
+    /// of the chain.
+    pub fn new(cert_chain: Vec<Certificate>, key: PrivateKey) -> Self {
+        Self::try_new(cert_chain, key).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8, RSA, or SEC1 private
+    /// key from the provided buffers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key.
+    pub fn try_read(
+        cert_chain_read: &mut dyn BufRead,
+        key_read: &mut dyn BufRead,
+    ) -> Result<Self, Error> {
+        let cert_chain = load::read_cert_chain(cert_chain_read)?;
+        let key = load::read_key(key_read)?;
+        Self::try_new(cert_chain, key)
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8, RSA, or SEC1 private
+    /// key from the provided buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read a valid certificate chain or private key.
+    pub fn read(cert_chain_read: &mut dyn BufRead, key_read: &mut dyn BufRead) -> Self {
+        Self::try_read(cert_chain_read, key_read).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8, RSA, or SEC1 private
+    /// key from the specified files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::TlsConfig;
+    /// # fn main() -> Result<(), myth::tls::Error> {
+    /// let config =
+    ///     TlsConfig::try_read_file("/path/to/certificate.pem", "/path/to/private/key.pem")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_read_file(
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let (cert_chain, key) = load::read_files(cert_chain_path.as_ref(), key_path.as_ref())?;
+        Self::try_new(cert_chain, key)
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8, RSA, or SEC1 private
+    /// key from the specified files.
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read a valid certificate chain or private key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::TlsConfig;
+    /// let config = TlsConfig::read_file("/path/to/certificate.pem", "/path/to/private/key.pem");
+    /// ```
+    pub fn read_file(cert_chain_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Self {
+        Self::try_read_file(cert_chain_path, key_path).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a new TLS config by reading both a certificate chain and a PKCS8, RSA, or SEC1
+    /// private key from a single buffer, in any order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key.
+    pub fn try_read_combined(read: &mut dyn BufRead) -> Result<Self, Error> {
+        let (cert_chain, key) = load::read_combined(read)?;
+        Self::try_new(cert_chain, key)
+    }
+
+    /// Creates a new TLS config by reading both a certificate chain and a PKCS8, RSA, or SEC1
+    /// private key from a single file, in any order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::TlsConfig;
+    /// # fn main() -> Result<(), myth::tls::Error> {
+    /// let config = TlsConfig::try_read_combined_file("/path/to/combined.pem")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_read_combined_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::try_read_combined(&mut load::open(path.as_ref())?)
+    }
+
+    /// Creates a new TLS config with a certificate for `hostnames`, signed by a certificate
+    /// authority that is generated in memory, for local development and tests.
+    ///
+    /// Hostnames that are IP addresses, such as `127.0.0.1`, are added to the certificate as IP
+    /// addresses. The returned [`SelfSigned`] has the certificate authority that clients must
+    /// trust, and can write it to a file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hostnames` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use myth::{Filter, TlsConfig};
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    ///
+    /// let (config, self_signed) = TlsConfig::self_signed(["localhost"]);
+    /// self_signed.write_ca_cert("ca.pem").unwrap();
+    ///
+    /// myth::serve(filter)
+    ///     .bind(([127, 0, 0, 1], 8443))
+    ///     .with_tls(config)
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    #[cfg(feature = "self-signed")]
+    #[cfg_attr(myth_docs, doc(cfg(feature = "self-signed")))]
+    pub fn self_signed(
+        hostnames: impl IntoIterator<Item = impl Into<String>>,
+    ) -> (Self, SelfSigned) {
+        let self_signed = SelfSigned::generate(hostnames.into_iter().map(Into::into).collect());
+        let config = Self::new(self_signed.cert_chain(), self_signed.key());
+        (config, self_signed)
+    }
+
+    /// Creates an [`SniBuilder`], which builds a TLS config that chooses a certificate based on
+    /// the server name that the client requests.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use myth::TlsConfig;
+    /// let config = TlsConfig::sni()
+    ///     .cert_file("example.com", "example.com/cert.pem", "example.com/key.pem")
+    ///     .cert_file("*.example.com", "wildcard/cert.pem", "wildcard/key.pem")
+    ///     .default_cert_file("default/cert.pem", "default/key.pem")
+    ///     .build();
+    /// ```
+    pub fn sni() -> SniBuilder {
+        SniBuilder {
+            resolver: SniResolver::default(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8 or RSA private key
+    /// from the specified files, along with a [`TlsReloader`] to read them again while the
+    /// server is running.
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read a valid certificate chain or private key.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use myth::{Filter, TlsConfig};
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    ///
+    /// let (config, reloader) =
+    ///     TlsConfig::read_file_reloadable("/path/to/certificate.pem", "/path/to/private/key.pem");
+    /// // Pick up renewed certificates.
+    /// reloader.watch(Duration::from_secs(60));
+    ///
+    /// myth::serve(filter)
+    ///     .bind(([0, 0, 0, 0], 443))
+    ///     .with_tls(config)
+    ///     .run()
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn read_file_reloadable(
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> (Self, TlsReloader) {
+        Self::sni()
+            .default_cert_file(cert_chain_path, key_path)
+            .build_reloadable()
+    }
+
+    /// Creates a new TLS config by reading a certificate chain and a PKCS8, RSA, or SEC1 private
+    /// key from the specified files, along with a [`TlsReloader`] to read them again while the
+    /// server is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key.
+    pub fn try_read_file_reloadable(
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Result<(Self, TlsReloader), Error> {
+        Self::sni()
+            .default_cert_file(cert_chain_path, key_path)
+            .try_build_reloadable()
+    }
+
+    /// Requests a certificate from clients, which is verified against the certificate
+    /// authorities in `ca_certs`.
+    ///
+    /// Depending on `mode`, clients without a certificate are either allowed to connect or fail
+    /// the handshake. The verified certificates are available to [`Filter`](crate::Filter)s
+    /// through [`peer_certificates()`].
+    ///
+    /// The certificates and ALPN protocols of this config are kept, but other settings of a
+    /// config created from a [`rustls::ServerConfig`] are reset to their defaults.
+    ///
+    /// Client certificates are not verified over QUIC, so HTTP/3 cannot be served with this
+    /// config, and [`Server::try_and_bind_http3()`](crate::Server::try_and_bind_http3) returns
+    /// [`Error::Http3Unavailable`](crate::server::Error::Http3Unavailable).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ca_certs` is empty or contains an invalid certificate.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use myth::tls::{ClientAuth, TlsConfig};
+    ///
+    /// let config = TlsConfig::read_file("/path/to/certificate.pem", "/path/to/private/key.pem")
+    ///     .client_auth_file(ClientAuth::Required, "/path/to/client/ca.pem");
+    /// ```
+    pub fn client_auth(self, mode: ClientAuth, ca_certs: Vec<Certificate>) -> Self {
+        assert!(
+            !ca_certs.is_empty(),
+            "no client certificate authorities provided"
+        );
+        let mut roots = RootCertStore::empty();
+        for cert in &ca_certs {
+            roots
+                .add(cert)
+                .unwrap_or_else(|error| panic!("invalid client certificate authority: {}", error));
+        }
+        let verifier = match mode {
+            ClientAuth::Optional => AllowAnyAnonymousOrAuthenticatedClient::new(roots),
+            ClientAuth::Required => AllowAnyAuthenticatedClient::new(roots),
+        };
+
+        let mut config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(verifier)
+            .with_cert_resolver(self.config.cert_resolver);
+        config.alpn_protocols = self.config.alpn_protocols;
+        Self {
+            config,
+            // QUIC handshakes do not verify client certificates, so HTTP/3 is left unavailable
+            // rather than serving clients without one.
+            #[cfg(feature = "http3")]
+            certs: None,
+            handshake_timeout: self.handshake_timeout,
+            max_pending_handshakes: self.max_pending_handshakes,
+        }
+    }
+
+    /// Requests a certificate from clients, which is verified against the certificate
+    /// authorities read from the specified file. See [`client_auth()`](Self::client_auth).
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read at least one valid certificate from the file.
+    pub fn client_auth_file(self, mode: ClientAuth, ca_path: impl AsRef<Path>) -> Self {
+        let ca_certs = load::open(ca_path.as_ref())
+            .and_then(|mut ca_read| load::read_cert_chain(&mut ca_read))
+            .unwrap_or_else(|error| panic!("{}", error));
+        self.client_auth(mode, ca_certs)
+    }
+
+    /// Sets how long to wait for the TLS handshake of a connection to complete before closing it.
+    ///
+    /// Connections are not counted by the [`ServerHandle`](crate::server::ServerHandle) until
+    /// their handshake has completed, so this bounds how long a client that never sends a
+    /// `ClientHello` can hold on to a connection.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum number of handshakes that are performed at once. While this many are
+    /// pending, no more connections are accepted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn max_pending_handshakes(mut self, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "the maximum number of pending handshakes must not be zero"
+        );
+        self.max_pending_handshakes = max;
+        self
+    }
+
+    /// Creates a QUIC server config with the same certificates, or [`None`] if the certificates
+    /// are unknown.
+    #[cfg(feature = "http3")]
+    pub(crate) fn quic_config(&self) -> Option<quinn::ServerConfig> {
+        self.certs.clone().map(quic::server_config)
+    }
+
+    /// Creates a new TLS config that resolves certificates with `resolver`, using ALPN
+    /// protocols for `h2` and `http/1.1`.
+    fn with_resolver(resolver: Arc<Reloadable>) -> Self {
+        let mut config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::clone(&resolver) as _);
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Self {
+            config,
+            #[cfg(feature = "http3")]
+            certs: Some(resolver),
+            handshake_timeout: HANDSHAKE_TIMEOUT,
+            max_pending_handshakes: MAX_PENDING_HANDSHAKES,
+        }
+    }
+}
+
+/// A builder for a [`TlsConfig`] that chooses a certificate based on the server name (SNI) that
+/// the client requests, created by [`TlsConfig::sni()`].
+///
+/// Names are matched without regard to case. An exact name takes precedence over a wildcard,
+/// and a wildcard such as `*.example.com` only matches a single label, such as
+/// `www.example.com`. Clients that do not send a server name, or send one without a matching
+/// certificate, get the default certificate, or fail the handshake if there is none.
+///
+/// The matched server name is available to [`Filter`](crate::Filter)s through
+/// [`server_name()`].
+pub struct SniBuilder {
+    resolver: SniResolver,
+    files: Vec<CertFile>,
+}
+
+/// A certificate chain and private key that are read from files by an [`SniBuilder`].
+#[derive(Debug)]
+struct CertFile {
+    /// The server name, or [`None`] for the default certificate.
+    name: Option<String>,
+    cert_chain_path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl SniBuilder {
+    /// Uses a certificate chain and private key for `name`, which may be a wildcard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the private key was invalid or does not match the first certificate of the
+    /// chain.
+    pub fn cert(mut self, name: &str, cert_chain: Vec<Certificate>, key: PrivateKey) -> Self {
+        let key = certified_key(cert_chain, &key);
+        self.resolver.insert(name, key);
+        self
+    }
+
+    /// Uses a certificate chain and a PKCS8, RSA, or SEC1 private key from the specified files
+    /// for `name`, which may be a wildcard.
+    ///
+    /// The files are read when the config is built, and again whenever it is reloaded.
+    pub fn cert_file(
+        mut self,
+        name: &str,
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Self {
+        self.files.push(CertFile {
+            name: Some(name.to_owned()),
+            cert_chain_path: cert_chain_path.as_ref().to_owned(),
+            key_path: key_path.as_ref().to_owned(),
+        });
+        self
+    }
+
+    /// Uses a certificate chain and private key for clients whose server name does not match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the private key was invalid or does not match the first certificate of the
+    /// chain.
+    pub fn default_cert(mut self, cert_chain: Vec<Certificate>, key: PrivateKey) -> Self {
+        self.resolver.default = Some(certified_key(cert_chain, &key));
+        self
+    }
+
+    /// Uses a certificate chain and a PKCS8, RSA, or SEC1 private key from the specified files
+    /// for clients whose server name does not match.
+    ///
+    /// The files are read when the config is built, and again whenever it is reloaded.
+    pub fn default_cert_file(
+        mut self,
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Self {
+        self.files.push(CertFile {
+            name: None,
+            cert_chain_path: cert_chain_path.as_ref().to_owned(),
+            key_path: key_path.as_ref().to_owned(),
+        });
+        self
+    }
+
+    /// Builds the [`TlsConfig`], using ALPN protocols for `h2` and `http/1.1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read a valid certificate chain or private key from the files.
+    pub fn build(self) -> TlsConfig {
+        self.try_build().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Builds the [`TlsConfig`], using ALPN protocols for `h2` and `http/1.1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key from the
+    /// files.
+    pub fn try_build(self) -> Result<TlsConfig, Error> {
+        let resolver = self.load()?;
+        Ok(TlsConfig::with_resolver(Arc::new(Reloadable::new(
+            resolver,
+        ))))
+    }
+
+    /// Builds the [`TlsConfig`] along with a [`TlsReloader`], which reads the certificate files
+    /// again to replace the certificates while the server is running.
+    ///
+    /// # Panics
+    ///
+    /// Panics upon failure to read a valid certificate chain or private key from the files.
+    pub fn build_reloadable(self) -> (TlsConfig, TlsReloader) {
+        self.try_build_reloadable()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Builds the [`TlsConfig`] along with a [`TlsReloader`], which reads the certificate files
+    /// again to replace the certificates while the server is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to read a valid certificate chain or private key from the
+    /// files.
+    pub fn try_build_reloadable(self) -> Result<(TlsConfig, TlsReloader), Error> {
+        let resolver = self.load()?;
+        let reloadable = Arc::new(Reloadable::new(resolver));
+        let config = TlsConfig::with_resolver(Arc::clone(&reloadable));
+        Ok((config, TlsReloader::new(self, reloadable)))
+    }
+
+    /// Reads the certificate files, returning a resolver with every certificate.
+    fn load(&self) -> Result<SniResolver, Error> {
+        let mut resolver = self.resolver.clone();
+        for file in &self.files {
+            let (cert_chain, key) = load::read_files(&file.cert_chain_path, &file.key_path)?;
+            let key = load::certified_key(cert_chain, &key)?;
+            match &file.name {
+                Some(name) => resolver.insert(name, key),
+                None => resolver.default = Some(key),
+            }
+        }
+        Ok(resolver)
+    }
+
+    /// Returns the paths of every certificate file and private key file.
+    fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .flat_map(|file| [&*file.cert_chain_path, &*file.key_path])
+    }
+}
+
+impl fmt::Debug for SniBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SniBuilder")
+            .field("resolver", &self.resolver)
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+fn certified_key(cert_chain: Vec<Certificate>, key: &PrivateKey) -> Arc<CertifiedKey> {
+    load::certified_key(cert_chain, key).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Lowercases a server name and removes any trailing dot.
+fn normalize_name(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Resolves certificates by server name, for an [`SniBuilder`].
+#[derive(Clone, Default)]
+struct SniResolver {
+    exact: HashMap<String, Arc<CertifiedKey>>,
+    /// Wildcard certificates, by the name without the leading `*.`.
+    wildcard: HashMap<String, Arc<CertifiedKey>>,
+    default: Option<Arc<CertifiedKey>>,
+}
+
+impl SniResolver {
+    fn insert(&mut self, name: &str, key: Arc<CertifiedKey>) {
+        let name = normalize_name(name);
+        match name.strip_prefix("*.") {
+            Some(suffix) => self.wildcard.insert(suffix.to_owned(), key),
+            None => self.exact.insert(name, key),
+        };
+    }
+
+    fn lookup(&self, server_name: Option<&str>) -> Option<&Arc<CertifiedKey>> {
+        server_name
+            .map(normalize_name)
+            .and_then(|name| {
+                self.exact.get(&name).or_else(|| {
+                    let (_, parent) = name.split_once('.')?;
+                    self.wildcard.get(parent)
+                })
+            })
+            .or(self.default.as_ref())
+    }
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.lookup(client_hello.server_name()).cloned()
+    }
+}
+
+impl fmt::Debug for SniResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SniResolver")
+            .field("exact", &self.exact.keys())
+            .field("wildcard", &self.wildcard.keys())
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+impl From<rustls::ServerConfig> for TlsConfig {
+    fn from(config: rustls::ServerConfig) -> Self {
+        Self {
+            config,
+            #[cfg(feature = "http3")]
+            certs: None,
+            handshake_timeout: HANDSHAKE_TIMEOUT,
+            max_pending_handshakes: MAX_PENDING_HANDSHAKES,
+        }
+    }
+}
+
+/// Information about the TLS session of a connection.
+#[derive(Clone, Debug)]
+pub struct TlsInfo {
+    protocol_version: Option<rustls::ProtocolVersion>,
+    cipher_suite: Option<rustls::CipherSuite>,
+    alpn_protocol: Option<Vec<u8>>,
+    server_name: Option<String>,
+    peer_certificates: Option<Arc<[PeerCertificate]>>,
+}
+
+impl TlsInfo {
+    fn new(connection: &rustls::ServerConnection) -> Self {
+        let peer_certificates = connection
+            .peer_certificates()
+            .map(|certs| certs.iter().cloned().map(PeerCertificate::new).collect());
+        Self {
+            protocol_version: connection.protocol_version(),
+            cipher_suite: connection
+                .negotiated_cipher_suite()
+                .map(|suite| suite.suite()),
+            alpn_protocol: connection.alpn_protocol().map(<[u8]>::to_vec),
+            server_name: connection.sni_hostname().map(str::to_owned),
+            peer_certificates,
+        }
+    }
+
+    /// Creates information about the TLS session of an HTTP/3 connection, which always uses
+    /// TLS 1.3.
+    #[cfg(feature = "http3")]
+    pub(crate) fn quic(connection: &quinn::Connection) -> Self {
+        let data = connection
+            .handshake_data()
+            .and_then(|data| data.downcast::<quinn::crypto::rustls::HandshakeData>().ok());
+        Self {
+            protocol_version: Some(rustls::ProtocolVersion::TLSv1_3),
+            cipher_suite: None,
+            alpn_protocol: data.as_ref().and_then(|data| data.protocol.clone()),
+            server_name: data.and_then(|data| data.server_name),
+            peer_certificates: None,
+        }
+    }
+
+    /// Returns the negotiated TLS protocol version, such as
+    /// [`TLSv1_3`](rustls::ProtocolVersion::TLSv1_3).
+    pub fn protocol_version(&self) -> Option<rustls::ProtocolVersion> {
+        self.protocol_version
+    }
+
+    /// Returns the negotiated cipher suite.
+    ///
+    /// This is [`None`] for HTTP/3 connections.
+    pub fn cipher_suite(&self) -> Option<rustls::CipherSuite> {
+        self.cipher_suite
+    }
+
+    /// Returns the protocol negotiated with ALPN, such as `h2`, or [`None`] if the client did not
+    /// use ALPN.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns the server name (SNI) that the client requested.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// Returns the verified certificate chain that the client presented, starting with the
+    /// certificate of the client itself.
+    ///
+    /// This is only present with [client authentication](TlsConfig::client_auth).
+    pub fn peer_certificates(&self) -> Option<&[PeerCertificate]> {
+        self.peer_certificates.as_deref()
+    }
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts the [`TlsInfo`] of the connection, or
+/// [`None`] if the connection does not use TLS.
+pub fn info() -> impl_Filter!(Option<Arc<TlsInfo>> => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| Outcome::Success((request.connection.tls.clone(),)))
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts the server name (SNI) that the client
+/// requested, or [`None`] if the connection does not use TLS or no name was sent.
+///
+/// # Example
+///
+/// ```
+/// use myth::{tls, Filter};
+///
+/// let filter = tls::server_name().handle(|name: Option<String>| async move {
+///     Ok(format!("Hello, {}!", name.as_deref().unwrap_or("stranger")))
+/// });
+/// ```
+pub fn server_name() -> impl_Filter!(Option<String> => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| {
+        let name = request
+            .connection
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.server_name.clone());
+        Outcome::Success((name,))
+    })
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts the verified certificate chain that the
+/// client presented, starting with the certificate of the client itself.
+///
+/// This extracts [`None`] if the connection does not use TLS or the client did not present a
+/// certificate, which requires [client authentication](TlsConfig::client_auth).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use myth::{
+///     tls::{self, PeerCertificate},
+///     Filter,
+/// };
+///
+/// let filter = tls::peer_certificates().handle(
+///     |certs: Option<Arc<[PeerCertificate]>>| async move {
+///         let name = certs
+///             .as_ref()
+///             .and_then(|certs| certs[0].common_name())
+///             .unwrap_or("anonymous")
+///             .to_owned();
+///         Ok(format!("Hello, {}!", name))
+///     },
+/// );
+/// ```
+pub fn peer_certificates() -> impl_Filter!(Option<Arc<[PeerCertificate]>> => Copy + (fmt::Debug)) {
+    ready_filter(|request, _| {
+        let certs = request
+            .connection
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.peer_certificates.clone());
+        Outcome::Success((certs,))
+    })
+}
+
+type Handshake<S> = Pin<Box<dyn Future<Output = Option<TlsStream<S>>> + Send>>;
+
+pin_project! {
+    /// An [`Incoming`] that performs a TLS handshake with each connection, created by
+    /// [`Server::with_tls()`](crate::Server::with_tls).
+    ///
+    /// Handshakes are performed with many connections at once, up to
+    /// [`TlsConfig::max_pending_handshakes()`], and a connection is only accepted once its
+    /// handshake has completed within [`TlsConfig::handshake_timeout()`].
+    pub struct TlsAcceptor<I: Accept> {
+        acceptor: tokio_rustls::TlsAcceptor,
+        #[pin]
+        incoming: I,
+        pending: FuturesUnordered<Handshake<I::Conn>>,
+        incoming_done: bool,
+        timeout: Duration,
+        max_pending: usize,
+    }
+}
+
+impl<I> TlsAcceptor<I>
+where
+    I: Accept,
+{
+    pub(crate) fn new(incoming: I, config: TlsConfig) -> Self {
+        Self {
+            acceptor: Arc::new(config.config).into(),
+            incoming,
+            pending: FuturesUnordered::new(),
+            incoming_done: false,
+            timeout: config.handshake_timeout,
+            max_pending: config.max_pending_handshakes,
+        }
+    }
+}
+
+impl<I> fmt::Debug for TlsAcceptor<I>
+where
+    I: Accept + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptor")
+            .field("incoming", &self.incoming)
+            .field("pending", &self.pending.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I> Incoming for TlsAcceptor<I>
+where
+    I: Incoming,
+    I::Conn: RequestStream,
+    I::Error: StdError + Send + Sync + 'static,
+{
+    type Addr = I::Addr;
+
+    fn local_addr(&self) -> Self::Addr {
+        self.incoming.local_addr()
+    }
+
+    fn set_tcp_options(&mut self, nodelay: bool, keepalive: Option<Duration>) {
+        self.incoming.set_tcp_options(nodelay, keepalive);
+    }
+}
+
+impl<I> Accept for TlsAcceptor<I>
+where
+    I: Accept,
+    I::Conn: RequestStream,
+{
+    type Conn = TlsStream<I::Conn>;
+
+    type Error = I::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            // Once `max_pending` handshakes are pending, the incoming connections wait until one
+            // of them completes, which wakes this task.
+            while !*this.incoming_done && this.pending.len() < *this.max_pending {
+                match this.incoming.as_mut().poll_accept(cx) {
+                    Poll::Ready(Some(Ok(stream))) => {
+                        let accept = this.acceptor.accept(stream);
+                        this.pending
+                            .push(Box::pin(handshake(accept, *this.timeout)));
+                    }
+                    Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                    Poll::Ready(None) => *this.incoming_done = true,
+                    Poll::Pending => break,
+                }
+            }
+
+            match Pin::new(&mut *this.pending).poll_next(cx) {
+                Poll::Ready(Some(Some(stream))) => return Poll::Ready(Some(Ok(stream))),
+                Poll::Ready(Some(None)) => {}
+                Poll::Ready(None) if *this.incoming_done => return Poll::Ready(None),
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Completes the TLS handshake of a connection, returning [`None`] if it failed or did not
+/// complete within `timeout`.
+async fn handshake<S>(accept: tokio_rustls::Accept<S>, timeout: Duration) -> Option<TlsStream<S>>
+where
+    S: RequestStream,
+{
+    match tokio::time::timeout(timeout, accept).await {
+        Ok(Ok(inner)) => {
+            let info = Arc::new(TlsInfo::new(inner.get_ref().1));
+            Some(TlsStream { inner, info })
+        }
+        Ok(Err(error)) => {
+            tracing::debug!("TLS handshake failed: {}", error);
+            None
+        }
+        Err(_) => {
+            tracing::debug!("Timed out performing TLS handshake");
+            None
+        }
+    }
+}
+
+/// A connection accepted by a [`TlsAcceptor`], whose handshake has completed.
+#[derive(Debug)]
+pub struct TlsStream<S> {
+    inner: tokio_rustls::server::TlsStream<S>,
+    info: Arc<TlsInfo>,
+}
+
+impl<S> TlsStream<S> {
+    /// Returns information about the TLS session.
+    pub fn info(&self) -> &TlsInfo {
+        &self.info
+    }
+}
+
+impl<S> RequestStream for TlsStream<S>
+where
+    S: RequestStream,
+{
+    fn remote_addr(&self) -> PeerAddr {
+        self.inner.get_ref().0.remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        self.inner.get_ref().0.local_addr()
+    }
+
+    fn proxy_header(&self) -> Option<Arc<ProxyHeader>> {
+        self.inner.get_ref().0.proxy_header()
+    }
+
+    fn tls_info(&self) -> Option<Arc<TlsInfo>> {
+        Some(Arc::clone(&self.info))
+    }
+}
+
+impl<S> AsyncRead for TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for TlsStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{server_name, TlsInfo};
+    use crate::test;
+
+    #[tokio::test]
+    async fn extract_server_name() {
+        test::get()
+            .success(&server_name(), |name| assert_eq!(name, None))
+            .await;
+
+        let info = Arc::new(TlsInfo {
+            protocol_version: None,
+            cipher_suite: None,
+            alpn_protocol: None,
+            server_name: Some("example.com".to_owned()),
+            peer_certificates: None,
+        });
+        test::get()
+            .tls(info)
+            .success(&server_name(), |name: Option<String>| {
+                assert_eq!(name.as_deref(), Some("example.com"));
+            })
+            .await;
+    }
+}
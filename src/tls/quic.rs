@@ -0,0 +1,108 @@
+use std::{fmt, sync::Arc};
+
+use quinn::{
+    crypto::rustls::QuicServerConfig,
+    rustls::{
+        self as quic_rustls,
+        crypto::ring,
+        pki_types::CertificateDer,
+        server::{ClientHello, ResolvesServerCert},
+        sign::{CertifiedKey, Signer, SigningKey},
+        version::TLS13,
+        SignatureAlgorithm, SignatureScheme,
+    },
+};
+use tokio_rustls::rustls::{self, sign};
+
+use super::reload::Reloadable;
+
+/// The ALPN protocol of HTTP/3.
+const ALPN_H3: &[u8] = b"h3";
+
+/// Creates a QUIC server config that uses the same certificates as a
+/// [`TlsConfig`](super::TlsConfig), including any that are reloaded.
+pub(super) fn server_config(certs: Arc<Reloadable>) -> quinn::ServerConfig {
+    let mut config =
+        quic_rustls::ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_protocol_versions(&[&TLS13])
+            .expect("QUIC requires TLS 1.3")
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(QuicResolver(certs)));
+    config.alpn_protocols = vec![ALPN_H3.to_vec()];
+    let config = QuicServerConfig::try_from(config).expect("no QUIC cipher suites");
+    quinn::ServerConfig::with_crypto(Arc::new(config))
+}
+
+/// Resolves certificates for QUIC handshakes, which use a newer version of Rustls than TCP
+/// handshakes, from the [`SniResolver`](super::SniResolver) of a [`TlsConfig`](super::TlsConfig).
+struct QuicResolver(Arc<Reloadable>);
+
+impl ResolvesServerCert for QuicResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let resolver = self.0.current();
+        let key = resolver.lookup(client_hello.server_name())?;
+        let cert_chain = key
+            .cert
+            .iter()
+            .map(|cert| CertificateDer::from(cert.0.clone()))
+            .collect();
+        Some(Arc::new(CertifiedKey::new(
+            cert_chain,
+            Arc::new(QuicSigningKey(Arc::clone(&key.key))),
+        )))
+    }
+}
+
+impl fmt::Debug for QuicResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("QuicResolver")
+            .field(&self.0.current())
+            .finish()
+    }
+}
+
+/// Signs QUIC handshakes with a private key of a [`TlsConfig`](super::TlsConfig).
+struct QuicSigningKey(Arc<dyn sign::SigningKey>);
+
+impl SigningKey for QuicSigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn Signer>> {
+        let offered = offered
+            .iter()
+            .map(|&scheme| rustls::SignatureScheme::from(u16::from(scheme)))
+            .collect::<Vec<_>>();
+        let signer = self.0.choose_scheme(&offered)?;
+        Some(Box::new(QuicSigner(signer)))
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::from(self.0.algorithm().get_u8())
+    }
+}
+
+impl fmt::Debug for QuicSigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("QuicSigningKey")
+            .field(&self.0.algorithm())
+            .finish()
+    }
+}
+
+struct QuicSigner(Box<dyn sign::Signer>);
+
+impl Signer for QuicSigner {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, quic_rustls::Error> {
+        self.0
+            .sign(message)
+            .map_err(|error| quic_rustls::Error::General(error.to_string()))
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::from(self.0.scheme().get_u16())
+    }
+}
+
+impl fmt::Debug for QuicSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("QuicSigner").field(&self.0.scheme()).finish()
+    }
+}
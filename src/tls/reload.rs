@@ -0,0 +1,128 @@
+use std::{
+    fmt, fs,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+
+use super::{SniBuilder, SniResolver};
+
+/// Resolves certificates with a [`SniResolver`] that can be replaced by a [`TlsReloader`].
+pub(super) struct Reloadable {
+    current: RwLock<Arc<SniResolver>>,
+}
+
+impl Reloadable {
+    pub(super) fn new(resolver: SniResolver) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(resolver)),
+        }
+    }
+
+    pub(super) fn current(&self) -> Arc<SniResolver> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+}
+
+impl ResolvesServerCert for Reloadable {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.current().resolve(client_hello)
+    }
+}
+
+/// A handle that reloads the certificates of a [`TlsConfig`](super::TlsConfig) from their
+/// files, created by [`TlsConfig::read_file_reloadable()`](super::TlsConfig::read_file_reloadable)
+/// or [`SniBuilder::build_reloadable()`].
+///
+/// New handshakes use the reloaded certificates, while connections that are already open keep
+/// using the previous ones.
+#[derive(Clone)]
+pub struct TlsReloader {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    source: SniBuilder,
+    reloadable: Arc<Reloadable>,
+}
+
+impl TlsReloader {
+    pub(super) fn new(source: SniBuilder, reloadable: Arc<Reloadable>) -> Self {
+        Self {
+            inner: Arc::new(Inner { source, reloadable }),
+        }
+    }
+
+    /// Reads the certificate files again, and uses them for new handshakes.
+    ///
+    /// If a valid certificate chain or private key could not be read, the error is logged and
+    /// the previous certificates are kept. Returns whether the certificates were replaced.
+    pub fn reload(&self) -> bool {
+        match self.inner.source.load() {
+            Ok(resolver) => {
+                *self.inner.reloadable.current.write().unwrap() = Arc::new(resolver);
+                tracing::info!("Reloaded TLS certificates");
+                true
+            }
+            Err(error) => {
+                tracing::error!("Failed to reload TLS certificates: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Starts checking the modification times of the certificate files every `interval`, and
+    /// [reloads](TlsReloader::reload) them once they change and have not changed again for
+    /// another `interval`, so that a certificate and key are not read halfway through being
+    /// replaced.
+    ///
+    /// This runs on a background task until the returned [`JoinHandle`] is aborted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn watch(&self, interval: Duration) -> JoinHandle<()> {
+        let reloader = self.clone();
+        let mut loaded = self.modified();
+        let mut previous = loaded.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let modified = reloader.modified();
+                if modified != loaded && modified == previous {
+                    reloader.reload();
+                    loaded = modified.clone();
+                }
+                previous = modified;
+            }
+        })
+    }
+
+    /// Returns the modification time of each file.
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        self.inner
+            .source
+            .paths()
+            .map(|path| {
+                fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for TlsReloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsReloader")
+            .field("source", &self.inner.source)
+            .finish_non_exhaustive()
+    }
+}
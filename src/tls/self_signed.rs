@@ -0,0 +1,213 @@
+use std::{fmt, fs, net::IpAddr, path::Path};
+
+use rcgen::{BasicConstraints, CertificateParams, DistinguishedName, DnType, IsCa, SanType};
+use tokio_rustls::rustls::{Certificate, PrivateKey};
+
+use super::load::{self, Error};
+
+/// A certificate authority and a certificate signed by it for local development, generated by
+/// [`TlsConfig::self_signed()`](super::TlsConfig::self_signed).
+///
+/// Clients must trust the certificate authority to connect without certificate errors.
+///
+/// # Example
+///
+/// ```no_run
+/// use myth::TlsConfig;
+///
+/// # async fn client() -> Result<(), Box<dyn std::error::Error>> {
+/// let (config, self_signed) = TlsConfig::self_signed(["localhost", "127.0.0.1"]);
+///
+/// // Trust it with `curl --cacert /tmp/myth-ca.pem`.
+/// self_signed.write_ca_cert("/tmp/myth-ca.pem")?;
+///
+/// // Or pin it in a test client.
+/// let client = reqwest::Client::builder()
+///     .add_root_certificate(reqwest::Certificate::from_pem(
+///         self_signed.ca_cert_pem().as_bytes(),
+///     )?)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(myth_docs, doc(cfg(feature = "self-signed")))]
+#[derive(Clone)]
+pub struct SelfSigned {
+    ca_cert: Certificate,
+    ca_cert_pem: String,
+    cert: Certificate,
+    cert_pem: String,
+    key: PrivateKey,
+    key_pem: String,
+}
+
+impl SelfSigned {
+    pub(super) fn generate(hostnames: Vec<String>) -> Self {
+        assert!(!hostnames.is_empty(), "no hostnames");
+
+        let mut params = CertificateParams::default();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name = DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "myth development CA");
+        let ca = rcgen::Certificate::from_params(params).expect("error generating CA certificate");
+
+        let mut params = CertificateParams::default();
+        params.distinguished_name = DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, hostnames[0].as_str());
+        params.subject_alt_names = hostnames
+            .into_iter()
+            .map(|hostname| match hostname.parse::<IpAddr>() {
+                Ok(ip) => SanType::IpAddress(ip),
+                Err(_) => SanType::DnsName(hostname),
+            })
+            .collect();
+        let leaf = rcgen::Certificate::from_params(params).expect("error generating certificate");
+
+        // Each serialization signs the certificate again, so the DER is read back from the PEM.
+        let ca_cert_pem = ca.serialize_pem().expect("error signing CA certificate");
+        let cert_pem = leaf
+            .serialize_pem_with_signer(&ca)
+            .expect("error signing certificate");
+        let ca_cert = first_cert(&ca_cert_pem);
+        let cert = first_cert(&cert_pem);
+
+        Self {
+            ca_cert,
+            ca_cert_pem,
+            cert,
+            cert_pem,
+            key: PrivateKey(leaf.serialize_private_key_der()),
+            key_pem: leaf.serialize_private_key_pem(),
+        }
+    }
+
+    pub(super) fn cert_chain(&self) -> Vec<Certificate> {
+        vec![self.cert.clone()]
+    }
+
+    pub(super) fn key(&self) -> PrivateKey {
+        self.key.clone()
+    }
+
+    /// Returns the certificate of the certificate authority, in DER.
+    pub fn ca_cert_der(&self) -> &[u8] {
+        &self.ca_cert.0
+    }
+
+    /// Returns the certificate of the certificate authority, in PEM.
+    pub fn ca_cert_pem(&self) -> &str {
+        &self.ca_cert_pem
+    }
+
+    /// Returns the certificate that the server presents, in DER.
+    pub fn cert_der(&self) -> &[u8] {
+        &self.cert.0
+    }
+
+    /// Returns the certificate that the server presents, in PEM.
+    pub fn cert_pem(&self) -> &str {
+        &self.cert_pem
+    }
+
+    /// Returns the PKCS8 private key of the certificate that the server presents, in PEM.
+    pub fn key_pem(&self) -> &str {
+        &self.key_pem
+    }
+
+    /// Writes the certificate of the certificate authority to a PEM file, so that clients such
+    /// as curl and browsers can be configured to trust it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to write the file.
+    pub fn write_ca_cert(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        write(path.as_ref(), &self.ca_cert_pem)
+    }
+
+    /// Writes the certificate that the server presents and its private key to PEM files, which
+    /// can be read again with [`TlsConfig::read_file()`](super::TlsConfig::read_file).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error upon failure to write either file.
+    pub fn write(
+        &self,
+        cert_chain_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> Result<(), Error> {
+        write(cert_chain_path.as_ref(), &self.cert_pem)?;
+        write(key_path.as_ref(), &self.key_pem)
+    }
+}
+
+impl fmt::Debug for SelfSigned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The private key is left out, so that it does not end up in logs.
+        f.debug_struct("SelfSigned")
+            .field("ca_cert_pem", &self.ca_cert_pem)
+            .field("cert_pem", &self.cert_pem)
+            .finish_non_exhaustive()
+    }
+}
+
+fn first_cert(pem: &str) -> Certificate {
+    load::read_cert_chain(&mut pem.as_bytes())
+        .expect("error reading generated certificate")
+        .remove(0)
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents).map_err(|error| Error::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_rustls::rustls::Certificate;
+    use x509_parser::{certificate::X509Certificate, prelude::FromDer};
+
+    use super::SelfSigned;
+    use crate::tls::{load, PeerCertificate, SubjectAltName};
+
+    #[test]
+    fn generate() {
+        let self_signed = SelfSigned::generate(vec!["localhost".to_owned(), "::1".to_owned()]);
+
+        let cert = PeerCertificate::new(Certificate(self_signed.cert_der().to_vec()));
+        assert_eq!(cert.common_name(), Some("localhost"));
+        assert_eq!(
+            cert.subject_alt_names(),
+            [
+                SubjectAltName::Dns("localhost".to_owned()),
+                SubjectAltName::Ip("::1".parse().unwrap()),
+            ]
+        );
+
+        let (_, ca) = X509Certificate::from_der(self_signed.ca_cert_der()).unwrap();
+        assert!(ca.is_ca());
+        let (_, leaf) = X509Certificate::from_der(self_signed.cert_der()).unwrap();
+        assert_eq!(leaf.issuer(), ca.subject());
+
+        load::certified_key(self_signed.cert_chain(), &self_signed.key()).unwrap();
+    }
+
+    #[test]
+    fn debug_without_key() {
+        let self_signed = SelfSigned::generate(vec!["localhost".to_owned()]);
+        let debug = format!("{:?}", self_signed);
+        assert!(debug.contains("cert_pem"));
+        assert!(!debug.contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    #[should_panic(expected = "no hostnames")]
+    fn no_hostnames() {
+        SelfSigned::generate(Vec::new());
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    fs, io,
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::UnixStream as StdUnixStream,
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{ready, FutureExt};
+use hyper::server::accept::Accept;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{UnixListener, UnixStream},
+    time::{sleep, Sleep},
+};
+
+use crate::{
+    service::{Incoming, RequestStream},
+    PeerAddr,
+};
+
+/// A configuration for a Unix domain socket listener, to be used with
+/// [`Server::bind_unix()`](crate::Server::bind_unix).
+///
+/// This can be created from a path, which uses the default options.
+///
+/// # Example
+///
+/// ```no_run
+/// use myth::{Filter, UnixConfig};
+///
+/// # #[tokio::main] async fn main() {
+/// let filter = myth::any().handle(|| async { Ok("Hello over a Unix socket!") });
+///
+/// let config = UnixConfig::new("/run/myth/myth.sock")
+///     // Allow the owner and group to connect.
+///     .mode(0o660);
+///
+/// myth::serve(filter).bind_unix(config).run().await;
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(myth_docs, doc(cfg(unix)))]
+pub struct UnixConfig {
+    path: PathBuf,
+    mode: Option<u32>,
+    remove_stale: bool,
+}
+
+impl UnixConfig {
+    /// Creates a new configuration for a socket at `path`.
+    ///
+    /// By default, a stale socket file at `path` is removed before binding, and the permissions of
+    /// the socket file are left unchanged.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: None,
+            remove_stale: true,
+        }
+    }
+
+    /// Sets the permissions of the socket file, such as `0o660`.
+    ///
+    /// The socket is bound inside a new directory next to the path that only the owner can access,
+    /// and is moved to the path once its permissions are set, so it can never be connected to
+    /// with other permissions. This requires the parent directory of the path to be writable.
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Sets whether a stale socket file should be removed before binding.
+    ///
+    /// A socket file is stale if nothing is listening on it. Files that are not sockets, and
+    /// sockets that are still in use, are never removed.
+    pub fn remove_stale(mut self, remove_stale: bool) -> Self {
+        self.remove_stale = remove_stale;
+        self
+    }
+
+    pub(crate) fn bind(self) -> io::Result<UnixIncoming> {
+        if self.remove_stale {
+            remove_stale(&self.path)?;
+        }
+        let listener = match self.mode {
+            Some(mode) => bind_with_mode(&self.path, mode)?,
+            None => UnixListener::bind(&self.path)?,
+        };
+        Ok(UnixIncoming {
+            listener,
+            path: self.path.into(),
+            timeout: None,
+        })
+    }
+}
+
+/// Binds a socket at `path` with `mode`, by binding it inside a private directory and then
+/// renaming it to `path`.
+fn bind_with_mode(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the socket path has no file name",
+        )
+    })?;
+    let mut dir_name = std::ffi::OsString::from(".");
+    dir_name.push(file_name);
+    dir_name.push(format!(".{}.tmp", std::process::id()));
+    let dir = path.with_file_name(dir_name);
+
+    fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let private = dir.join(file_name);
+    let result = UnixListener::bind(&private).and_then(|listener| {
+        fs::set_permissions(&private, fs::Permissions::from_mode(mode))?;
+        fs::rename(&private, path)?;
+        Ok(listener)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&private);
+    }
+    let _ = fs::remove_dir(&dir);
+    result
+}
+
+impl From<PathBuf> for UnixConfig {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&Path> for UnixConfig {
+    fn from(path: &Path) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<String> for UnixConfig {
+    fn from(path: String) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&str> for UnixConfig {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+fn remove_stale(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    if !metadata.file_type().is_socket() {
+        return Ok(());
+    }
+    match StdUnixStream::connect(path) {
+        Ok(_) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+            tracing::debug!("Removing stale socket file {}", path.display());
+            fs::remove_file(path)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// An [`Incoming`] that accepts connections on a Unix domain socket.
+///
+/// The socket file is removed when this is dropped.
+#[derive(Debug)]
+#[cfg_attr(myth_docs, doc(cfg(unix)))]
+pub struct UnixIncoming {
+    listener: UnixListener,
+    path: Arc<Path>,
+    timeout: Option<Pin<Box<Sleep>>>,
+}
+
+impl Incoming for UnixIncoming {
+    type Addr = PeerAddr;
+
+    fn local_addr(&self) -> PeerAddr {
+        PeerAddr::Unix(Some(self.path.to_path_buf()))
+    }
+}
+
+impl Accept for UnixIncoming {
+    type Conn = UnixConnection;
+
+    type Error = io::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        if let Some(timeout) = &mut self.timeout {
+            ready!(timeout.poll_unpin(cx));
+            self.timeout = None;
+        }
+        loop {
+            match ready!(self.listener.poll_accept(cx)) {
+                Ok((stream, _)) => {
+                    return Poll::Ready(Some(Ok(UnixConnection {
+                        stream,
+                        path: Arc::clone(&self.path),
+                    })))
+                }
+                Err(error) if is_connection_error(&error) => {
+                    tracing::debug!("Accepted connection already errored: {}", error);
+                }
+                Err(error) => {
+                    tracing::error!("Accept error: {}", error);
+                    let mut timeout = Box::pin(sleep(Duration::from_secs(1)));
+                    if timeout.poll_unpin(cx).is_pending() {
+                        self.timeout = Some(timeout);
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for UnixIncoming {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.path) {
+            tracing::debug!(
+                "Failed to remove socket file {}: {}",
+                self.path.display(),
+                error
+            );
+        }
+    }
+}
+
+fn is_connection_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+/// A connection accepted by a [`UnixIncoming`].
+///
+/// The local address is the path that the listener was configured with, which may differ from
+/// the path that the socket was bound at.
+#[derive(Debug)]
+pub struct UnixConnection {
+    stream: UnixStream,
+    path: Arc<Path>,
+}
+
+impl RequestStream for UnixConnection {
+    fn remote_addr(&self) -> PeerAddr {
+        let path = self
+            .stream
+            .peer_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(Path::to_owned));
+        PeerAddr::Unix(path)
+    }
+
+    fn local_addr(&self) -> Option<PeerAddr> {
+        Some(PeerAddr::Unix(Some(self.path.to_path_buf())))
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+}
@@ -0,0 +1,283 @@
+//! Connection upgrades for custom protocols.
+//!
+//! [`on()`] matches requests that ask to switch protocols with the `Upgrade` header, while
+//! [`connect()`] matches `CONNECT` requests. Both produce an [`Upgrade`], which creates the
+//! response and hands the [`Upgraded`] connection to a handler once the protocol has been switched.
+//!
+//! # Example
+//!
+//! ```
+//! use myth::{upgrade::Upgrade, Filter};
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! let filter = myth::upgrade::on("echo").handle(|upgrade: Upgrade| async move {
+//!     Ok(upgrade.on_upgrade(|mut io| async move {
+//!         let mut buf = [0; 1024];
+//!         while let Ok(len @ 1..) = io.read(&mut buf).await {
+//!             if io.write_all(&buf[..len]).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!     }))
+//! });
+//! ```
+
+use std::{convert::TryFrom, fmt, future::Future};
+
+use hyper::upgrade::OnUpgrade;
+pub use hyper::upgrade::Upgraded;
+use tracing::{Instrument, Span};
+
+use crate::{
+    errors::FilterError,
+    filter::ready::ready_filter,
+    forward::AttemptedMethods,
+    header::{self, HeaderName, HeaderValue},
+    impl_Filter,
+    method::Method,
+    outcome::Outcome,
+    request::{Request, RequestState},
+    response::default_response,
+    util::StrExt,
+    Forwarding, Responder, Response, StatusCode,
+};
+
+static CONNECTION_UPGRADE: HeaderValue = HeaderValue::from_static("upgrade");
+
+/// Creates a [`Filter`](crate::Filter) that matches requests with an `Upgrade` header
+/// containing `protocol`, compared case-insensitively.
+///
+/// Requests that do not ask for `protocol` are forwarded.
+///
+/// # Errors
+///
+/// Errors with an [`Error`] if the `Connection` header does not contain `upgrade`, or if the
+/// connection cannot be upgraded.
+///
+/// # Panics
+///
+/// Panics if `protocol` is not a valid header value.
+pub fn on(protocol: impl Into<String>) -> impl_Filter!(Upgrade => Clone + (fmt::Debug)) {
+    let protocol = protocol.into();
+    let value = HeaderValue::try_from(protocol.as_str()).expect("invalid upgrade protocol");
+
+    ready_filter(move |request, request_state| {
+        if !has_token(request, header::UPGRADE, &protocol) {
+            return Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::NotFound,
+            };
+        }
+        if !has_token(request, header::CONNECTION, "upgrade") {
+            return Outcome::Error(Error::MissingConnectionUpgrade.into());
+        }
+        match take_on_upgrade(request_state) {
+            Ok(on_upgrade) => Outcome::Success((Upgrade {
+                on_upgrade,
+                protocol: Some(value.clone()),
+            },)),
+            Err(error) => Outcome::Error(error.into()),
+        }
+    })
+}
+
+/// Creates a [`Filter`](crate::Filter) that matches `CONNECT` requests.
+///
+/// Requests with other methods are forwarded as
+/// [`MethodNotAllowed`](Forwarding::MethodNotAllowed).
+///
+/// # Errors
+///
+/// Errors with an [`Error`] if the connection cannot be upgraded.
+pub fn connect() -> impl_Filter!(Upgrade => Copy + (fmt::Debug)) {
+    ready_filter(|request, request_state| {
+        if request.method != Method::CONNECT {
+            return Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::MethodNotAllowed(AttemptedMethods::CONNECT),
+            };
+        }
+        match take_on_upgrade(request_state) {
+            Ok(on_upgrade) => Outcome::Success((Upgrade {
+                on_upgrade,
+                protocol: None,
+            },)),
+            Err(error) => Outcome::Error(error.into()),
+        }
+    })
+}
+
+/// A request to upgrade the connection, produced by [`on()`] or [`connect()`].
+pub struct Upgrade {
+    on_upgrade: OnUpgrade,
+    protocol: Option<HeaderValue>,
+}
+
+impl Upgrade {
+    /// Returns the protocol that was matched by [`on()`], or [`None`] for [`connect()`].
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.protocol.as_ref()
+    }
+
+    /// Creates the default response, and calls `func` with the [`Upgraded`] connection once the
+    /// protocol has been switched.
+    ///
+    /// For [`on()`], this is `101 Switching Protocols` with the `Connection` and `Upgrade` headers.
+    /// For [`connect()`], this is an empty `200 OK`.
+    ///
+    /// `func` is run on a separate task.
+    pub fn on_upgrade<F, Fut>(self, func: F) -> Response
+    where
+        F: FnOnce(Upgraded) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let response = match &self.protocol {
+            Some(protocol) => Response::default()
+                .with_status(StatusCode::SWITCHING_PROTOCOLS)
+                .with_header(header::CONNECTION, CONNECTION_UPGRADE.clone())
+                .with_header(header::UPGRADE, protocol),
+            None => Response::default(),
+        };
+        self.on_upgrade_with(response, func)
+    }
+
+    /// Uses `responder` as the response, and calls `func` with the [`Upgraded`] connection once the
+    /// protocol has been switched.
+    ///
+    /// The connection is only upgraded if the response has a status of `101 Switching Protocols`,
+    /// or a successful status for [`connect()`]. Otherwise, `func` is never called.
+    ///
+    /// `func` is run on a separate task.
+    pub fn on_upgrade_with<F, Fut>(self, responder: impl Responder, func: F) -> Response
+    where
+        F: FnOnce(Upgraded) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let response = responder.into_response();
+        let switches = match self.protocol {
+            Some(_) => response.status() == StatusCode::SWITCHING_PROTOCOLS,
+            None => response.status().is_success(),
+        };
+        if switches {
+            let span = tracing::trace_span!("Upgraded connection", protocol = ?self.protocol);
+            spawn(self.on_upgrade, span, func);
+        }
+        response
+    }
+}
+
+impl fmt::Debug for Upgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgrade")
+            .field("protocol", &self.protocol)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns whether the comma-separated header `name` contains `token`, compared
+/// case-insensitively.
+pub(crate) fn has_token(request: &Request, name: HeaderName, token: &str) -> bool {
+    request
+        .header_all(name)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|item| item.trim_spaces_tabs().eq_ignore_ascii_case(token))
+}
+
+pub(crate) fn take_on_upgrade(request_state: &mut RequestState) -> Result<OnUpgrade, Error> {
+    request_state
+        .on_upgrade()
+        .ok_or(Error::ConnectionNotUpgradable)
+}
+
+/// Spawns a task that waits for `on_upgrade` and then runs `func`.
+pub(crate) fn spawn<F, Fut>(on_upgrade: OnUpgrade, span: Span, func: F)
+where
+    F: FnOnce(Upgraded) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(
+        async move {
+            match on_upgrade.await {
+                Ok(upgraded) => func(upgraded).await,
+                Err(error) => tracing::debug!("Failed to upgrade connection: {}", error),
+            }
+        }
+        .instrument(span),
+    );
+}
+
+/// An error for the [`on()`] and [`connect()`] filters.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The `Connection` header did not contain `upgrade`.
+    MissingConnectionUpgrade,
+
+    /// The underlying connection does not support upgrades, such as for HTTP/2 requests.
+    ConnectionNotUpgradable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingConnectionUpgrade => write!(f, "missing `Connection: upgrade` header"),
+            Self::ConnectionNotUpgradable => write!(f, "connection cannot be upgraded"),
+        }
+    }
+}
+
+impl FilterError for Error {
+    fn into_response(self: Box<Self>) -> Response {
+        tracing::debug!("default response for upgrade error: {}", self);
+        default_response(StatusCode::BAD_REQUEST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connect, on, Error};
+    use crate::{header, test, Forwarding};
+
+    #[tokio::test]
+    async fn other_protocol() {
+        test::get().not_found(&on("foo")).await;
+        test::get()
+            .header(header::UPGRADE, "bar, baz")
+            .header(header::CONNECTION, "upgrade")
+            .not_found(&on("foo"))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn missing_connection_upgrade() {
+        let error: Error = test::get()
+            .header(header::UPGRADE, "bar, FOO")
+            .header(header::CONNECTION, "keep-alive")
+            .error(&on("foo"))
+            .await;
+        assert!(matches!(error, Error::MissingConnectionUpgrade));
+    }
+
+    #[tokio::test]
+    async fn not_upgradable() {
+        let error: Error = test::get()
+            .header(header::UPGRADE, "foo")
+            .header(header::CONNECTION, "Upgrade")
+            .error(&on("foo"))
+            .await;
+        assert!(matches!(error, Error::ConnectionNotUpgradable));
+    }
+
+    #[tokio::test]
+    async fn connect_method() {
+        let forwarding = test::get().forwarding(&connect()).await;
+        assert!(matches!(forwarding, Forwarding::MethodNotAllowed(_)));
+
+        let error: Error = test::connect()
+            .uri("example.com:443")
+            .error(&connect())
+            .await;
+        assert!(matches!(error, Error::ConnectionNotUpgradable));
+    }
+}
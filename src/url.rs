@@ -0,0 +1,449 @@
+//! Named routes and URL generation.
+//!
+//! [`Routes`] names route patterns, which use the syntax of [`Router`](crate::Router)
+//! patterns. Once they are [mounted](mount) under a path, or given to a
+//! [`Router`](crate::Router) with [`Router::name()`](crate::Router::name), the [`url_for()`]
+//! filter extracts a [`UrlFor`] that generates URLs from a name and parameters. The URLs
+//! include the path that the routes are mounted under, and the parameters are percent-encoded.
+//!
+//! Naming the [`template()`](crate::path::template) that matches a route, or naming routes of
+//! a [`Router`](crate::Router), keeps the generated URLs in sync with the routes.
+//!
+//! # Example
+//!
+//! ```
+//! use myth::{url::{self, Routes, UrlFor}, Filter};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     id: u64,
+//! }
+//!
+//! let settings = myth::path::template::<User>("/users/{id}/settings");
+//! let routes = Routes::new().template("settings", &settings);
+//!
+//! let settings = settings.handle(|user: User| async move {
+//!     Ok(format!("Settings of {}", user.id))
+//! });
+//! let home = myth::path!().and(url::url_for()).handle(|url_for: UrlFor| async move {
+//!     // `/app/users/42/settings` when mounted under `/app`.
+//!     Ok(url_for.url("settings", (42,))?)
+//! });
+//!
+//! let filter = myth::path!("app" / ..)
+//!     .and(url::mount(routes))
+//!     .and(settings.or(home));
+//! ```
+
+use std::{collections::HashMap, error::Error as StdError, fmt, fmt::Display, sync::Arc};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::{
+    filter::ready::ready_filter,
+    impl_Filter,
+    outcome::Outcome,
+    path::Template,
+    router::{parse_pattern, Segment},
+};
+
+/// The characters that are percent-encoded in the segments of generated URLs.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// The characters that are percent-encoded in catch-all parameters, which keep their slashes.
+const CATCH_ALL: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Route patterns by name, for generating URLs with [`UrlFor`].
+///
+/// Routes matched with a [`template()`](crate::path::template) are named with
+/// [`template()`](Self::template), which takes the pattern from the template itself. Routes
+/// matched with [`path!`](crate::path!) or [`path`](crate::path) filters can be named with
+/// their equivalent [`Router`](crate::Router) pattern, such as `/users/{id}` for
+/// `path!("users" / u64)`, but nothing checks that the two agree. Parameters are given to
+/// [`UrlFor::url()`] in order, so their names only serve as documentation.
+#[derive(Clone, Debug, Default)]
+pub struct Routes {
+    patterns: HashMap<String, Arc<[Segment]>>,
+}
+
+impl Routes {
+    /// Creates an empty set of routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names a route pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is invalid, or if the name is already used.
+    pub fn route(mut self, name: impl Into<String>, pattern: &str) -> Self {
+        self.insert(name.into(), parse_pattern(pattern).into());
+        self
+    }
+
+    /// Names the pattern of `template`, so that the URLs generated for it match the template.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is already used.
+    pub fn template<T>(mut self, name: impl Into<String>, template: &Template<T>) -> Self {
+        self.insert(name.into(), template.segments());
+        self
+    }
+
+    pub(crate) fn insert(&mut self, name: String, segments: Arc<[Segment]>) {
+        assert!(
+            !self.patterns.contains_key(&name),
+            "duplicate route name {:?}",
+            name
+        );
+        self.patterns.insert(name, segments);
+    }
+
+    /// Returns whether no routes are named.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// Generates URLs for named [`Routes`], extracted by [`url_for()`].
+#[derive(Clone, Debug)]
+pub struct UrlFor {
+    routes: Arc<Routes>,
+    prefix: String,
+}
+
+impl UrlFor {
+    pub(crate) fn new(routes: Arc<Routes>, prefix: impl Into<String>) -> Self {
+        Self {
+            routes,
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Returns the path that the routes are mounted under, which starts every generated URL.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Generates the URL of the route `name`, with `params` for its parameters in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no route `name`, if the number of parameters does not match
+    /// its pattern, or if a parameter is empty, as it would not match. Parameters that are `.` or
+    /// `..`, or catch-alls with such segments, are also errors, as clients would remove them
+    /// from the URL even when percent-encoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use myth::url::UrlFor;
+    /// # fn example(url_for: UrlFor) -> Result<(), myth::url::UrlError> {
+    /// // `/users/42/posts/hello%20world`
+    /// let url = url_for.url("post", (42, "hello world"))?;
+    /// // `/`
+    /// let url = url_for.url("home", ())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn url(&self, name: &str, params: impl UrlParams) -> Result<String, UrlError> {
+        let segments = self
+            .routes
+            .patterns
+            .get(name)
+            .ok_or_else(|| UrlError::UnknownRoute(name.to_owned()))?;
+        let params = params.into_strings();
+        let expected = segments
+            .iter()
+            .filter(|segment| !matches!(segment, Segment::Literal(_)))
+            .count();
+        if params.len() != expected {
+            return Err(UrlError::WrongParams {
+                route: name.to_owned(),
+                expected,
+                found: params.len(),
+            });
+        }
+
+        let mut url = self.prefix.clone();
+        let mut params = params.into_iter();
+        for segment in segments.iter() {
+            url.push('/');
+            let (param, value, set) = match segment {
+                Segment::Literal(literal) => {
+                    url.extend(utf8_percent_encode(literal, SEGMENT));
+                    continue;
+                }
+                Segment::Param(param) => (param, params.next(), SEGMENT),
+                Segment::CatchAll(param) => (param, params.next(), CATCH_ALL),
+            };
+            let value = value.expect("the number of parameters was checked");
+            if value.is_empty() {
+                return Err(UrlError::EmptyParam {
+                    route: name.to_owned(),
+                    param: param.to_string(),
+                });
+            }
+            if value
+                .split('/')
+                .any(|segment| segment == "." || segment == "..")
+            {
+                return Err(UrlError::DotSegment {
+                    route: name.to_owned(),
+                    param: param.to_string(),
+                });
+            }
+            url.extend(utf8_percent_encode(&value, set));
+        }
+        if url.is_empty() {
+            url.push('/');
+        }
+        Ok(url)
+    }
+}
+
+/// Parameters for [`UrlFor::url()`]: a tuple of up to 12 values that implement [`Display`].
+pub trait UrlParams {
+    /// Formats the parameters.
+    fn into_strings(self) -> Vec<String>;
+}
+
+macro_rules! impl_url_params {
+    ($($params:ident),*) => {
+        impl<$($params: Display),*> UrlParams for ($($params,)*) {
+            #[allow(non_snake_case)]
+            fn into_strings(self) -> Vec<String> {
+                let ($($params,)*) = self;
+                vec![$($params.to_string()),*]
+            }
+        }
+    };
+}
+
+impl_url_params!();
+impl_url_params!(T1);
+impl_url_params!(T1, T2);
+impl_url_params!(T1, T2, T3);
+impl_url_params!(T1, T2, T3, T4);
+impl_url_params!(T1, T2, T3, T4, T5);
+impl_url_params!(T1, T2, T3, T4, T5, T6);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_url_params!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+
+/// An error while generating a URL with [`UrlFor`], or extracting it with [`url_for()`].
+///
+/// These are mistakes in the application, so unless it is recovered, this results in a
+/// `500 Internal Server Error`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum UrlError {
+    /// No [`Routes`] were [mounted](mount) before [`url_for()`].
+    NotMounted,
+    /// There is no route with the name.
+    UnknownRoute(String),
+    /// The number of parameters does not match the pattern of the route.
+    WrongParams {
+        route: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A parameter is empty.
+    EmptyParam { route: String, param: String },
+    /// A parameter is, or a catch-all has, a `.` or `..` segment.
+    DotSegment { route: String, param: String },
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotMounted => write!(f, "no routes were mounted for `url_for()`"),
+            Self::UnknownRoute(route) => write!(f, "unknown route {:?}", route),
+            Self::WrongParams {
+                route,
+                expected,
+                found,
+            } => write!(
+                f,
+                "route {:?} has {} parameters, but {} were given",
+                route, expected, found
+            ),
+            Self::EmptyParam { route, param } => {
+                write!(f, "parameter {:?} of route {:?} is empty", param, route)
+            }
+            Self::DotSegment { route, param } => write!(
+                f,
+                "parameter {:?} of route {:?} has a `.` or `..` segment",
+                param, route
+            ),
+        }
+    }
+}
+
+impl StdError for UrlError {}
+
+/// Creates a [`Filter`](crate::Filter) that makes `routes` available to [`url_for()`], under
+/// the part of the path that has been matched so far.
+pub fn mount(routes: Routes) -> impl_Filter!(() => Clone + (fmt::Debug)) {
+    let routes = Arc::new(routes);
+    ready_filter(move |request, request_state| {
+        let prefix = request_state.previous_path(request).trim_end_matches('/');
+        request_state.url_for = Some(UrlFor::new(Arc::clone(&routes), prefix));
+        Outcome::Success(())
+    })
+}
+
+/// Creates a [`Filter`](crate::Filter) that extracts a [`UrlFor`] for the [`Routes`] that were
+/// mounted most recently, with [`mount()`] or by a [`Router`](crate::Router).
+///
+/// Errors with [`UrlError::NotMounted`] if there are none.
+pub fn url_for() -> impl_Filter!(UrlFor => Copy + (fmt::Debug)) {
+    ready_filter(|_, request_state| match &request_state.url_for {
+        Some(url_for) => Outcome::Success((url_for.clone(),)),
+        None => Outcome::Error(UrlError::NotMounted.into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{mount, url_for, Routes, UrlError, UrlFor};
+    use crate::{router, test, Filter, Router};
+
+    fn routes() -> Routes {
+        Routes::new()
+            .route("home", "/")
+            .route("user", "/users/{id}")
+            .route("post", "/users/{id}/posts/{post}")
+            .route("file", "/files/{*path}")
+            .route("spaced", "/a b/{id}")
+    }
+
+    #[test]
+    fn urls() {
+        let url_for = UrlFor::new(Arc::new(routes()), "");
+        assert_eq!(url_for.url("home", ()).unwrap(), "/");
+        assert_eq!(url_for.url("user", (42,)).unwrap(), "/users/42");
+        assert_eq!(
+            url_for.url("post", (42, "a/b c?d")).unwrap(),
+            "/users/42/posts/a%2Fb%20c%3Fd"
+        );
+        assert_eq!(
+            url_for.url("file", ("css/main 1.css",)).unwrap(),
+            "/files/css/main%201.css"
+        );
+        assert_eq!(url_for.url("spaced", ('x',)).unwrap(), "/a%20b/x");
+
+        let url_for = UrlFor::new(Arc::new(routes()), "/app");
+        assert_eq!(url_for.url("home", ()).unwrap(), "/app");
+        assert_eq!(url_for.url("user", (42,)).unwrap(), "/app/users/42");
+
+        assert!(matches!(
+            url_for.url("missing", ()),
+            Err(UrlError::UnknownRoute(_))
+        ));
+        assert!(matches!(
+            url_for.url("post", (42,)),
+            Err(UrlError::WrongParams {
+                expected: 2,
+                found: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            url_for.url("user", ("",)),
+            Err(UrlError::EmptyParam { .. })
+        ));
+        for (route, param) in [("user", ".."), ("user", "."), ("file", "css/../secret")] {
+            assert!(matches!(
+                url_for.url(route, (param,)),
+                Err(UrlError::DotSegment { .. })
+            ));
+        }
+        assert_eq!(url_for.url("user", ("..a",)).unwrap(), "/app/users/..a");
+    }
+
+    #[tokio::test]
+    async fn template() {
+        #[derive(serde::Deserialize)]
+        struct Post {
+            id: u64,
+            post: String,
+        }
+
+        let template = crate::path::template::<Post>("/users/{id}/posts/{post}");
+        let routes = Routes::new().template("post", &template);
+        let filter = mount(routes).and(url_for()).and(template);
+        test::get()
+            .uri("/users/5/posts/a%20b")
+            .success(&filter, |url_for: UrlFor, post: Post| {
+                assert_eq!(
+                    url_for.url("post", (post.id, post.post)).unwrap(),
+                    "/users/5/posts/a%20b"
+                );
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn mounted() {
+        let filter = crate::path!("app" / ..).and(mount(routes())).and(url_for());
+        test::get()
+            .uri("/app/users")
+            .success(&filter, |url_for: UrlFor| {
+                assert_eq!(url_for.url("user", (5,)).unwrap(), "/app/users/5");
+            })
+            .await;
+
+        let error: UrlError = test::get().error(&url_for()).await;
+        assert!(matches!(error, UrlError::NotMounted));
+    }
+
+    #[tokio::test]
+    async fn router() {
+        let router = Router::new()
+            .get(
+                "/users/{id}",
+                router::param::<u64>("id").and(url_for()).handle(
+                    |id: u64, url_for: UrlFor| async move { Ok(url_for.url("posts", (id,))?) },
+                ),
+            )
+            .name("user")
+            .get(
+                "/users/{id}/posts",
+                crate::any().handle(|| async { Ok("") }),
+            )
+            .name("posts");
+        let filter = crate::path!("api" / ..).and(router);
+        let response = test::get().uri("/api/users/5").response(&filter).await;
+        assert_eq!(response.body(), "/api/users/5/posts");
+    }
+}
@@ -0,0 +1,321 @@
+//! WebSocket connections.
+//!
+//! The [`ws()`] [`Filter`](crate::Filter) matches a WebSocket handshake request. Once matched,
+//! [`Ws::on_upgrade`] responds with `101 Switching Protocols` and hands a [`WebSocket`] to a
+//! handler after the connection has been upgraded.
+//!
+//! # Example
+//!
+//! ```
+//! use futures_util::{SinkExt, StreamExt};
+//! use myth::{ws::Ws, Filter};
+//!
+//! // Echo every message back to the client.
+//! let filter = myth::ws::ws().handle(|ws: Ws| async move {
+//!     Ok(ws.on_upgrade(|mut socket| async move {
+//!         while let Some(Ok(message)) = socket.next().await {
+//!             if socket.send(message).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!     }))
+//! });
+//! ```
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{Sink, Stream};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+pub use tokio_tungstenite::tungstenite::{
+    protocol::{frame::CloseFrame, WebSocketConfig},
+    Error as ProtocolError, Message,
+};
+use tokio_tungstenite::{
+    tungstenite::{handshake::derive_accept_key, protocol::Role},
+    WebSocketStream,
+};
+
+use crate::{
+    errors::FilterError,
+    filter::ready::ready_filter,
+    forward::AttemptedMethods,
+    header::{self, HeaderValue},
+    impl_Filter,
+    method::Method,
+    outcome::Outcome,
+    request::{Request, RequestState},
+    response::default_response,
+    upgrade::{self, has_token},
+    Forwarding, Responder, Response, StatusCode,
+};
+
+static WEBSOCKET: HeaderValue = HeaderValue::from_static("websocket");
+static CONNECTION_UPGRADE: HeaderValue = HeaderValue::from_static("upgrade");
+static VERSION_13: HeaderValue = HeaderValue::from_static("13");
+
+/// Creates a [`Filter`](crate::Filter) that matches a WebSocket handshake request.
+///
+/// Requests without an `Upgrade: websocket` header are forwarded. Handshakes that use a method
+/// other than `GET` are forwarded as [`MethodNotAllowed`](Forwarding::MethodNotAllowed).
+///
+/// # Errors
+///
+/// Errors with an [`Error`] if the handshake headers are invalid, or if the connection cannot be
+/// upgraded.
+pub fn ws() -> impl_Filter!(Ws => Copy + (fmt::Debug)) {
+    ready_filter(|request, request_state| {
+        if !has_token(request, header::UPGRADE, "websocket") {
+            return Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::NotFound,
+            };
+        }
+        if request.method != Method::GET {
+            return Outcome::Forward {
+                input: (),
+                forwarding: Forwarding::MethodNotAllowed(AttemptedMethods::GET),
+            };
+        }
+        match handshake(request, request_state) {
+            Ok(ws) => Outcome::Success((ws,)),
+            Err(error) => Outcome::Error(error.into()),
+        }
+    })
+}
+
+fn handshake(request: &Request, request_state: &mut RequestState) -> Result<Ws, Error> {
+    if !has_token(request, header::CONNECTION, "upgrade") {
+        return Err(Error::MissingConnectionUpgrade);
+    }
+
+    if request.header(header::SEC_WEBSOCKET_VERSION) != Some(&VERSION_13) {
+        return Err(Error::UnsupportedVersion);
+    }
+
+    let key = request
+        .header(header::SEC_WEBSOCKET_KEY)
+        .ok_or(Error::MissingKey)?;
+    let accept = HeaderValue::try_from(derive_accept_key(key.as_bytes()))
+        .expect("Sec-WebSocket-Accept should be valid base64");
+
+    let on_upgrade = request_state
+        .on_upgrade()
+        .ok_or(Error::ConnectionNotUpgradable)?;
+
+    Ok(Ws {
+        on_upgrade,
+        accept,
+        config: None,
+    })
+}
+
+/// A WebSocket handshake that has been accepted by [`ws()`].
+pub struct Ws {
+    on_upgrade: OnUpgrade,
+    accept: HeaderValue,
+    config: Option<WebSocketConfig>,
+}
+
+impl Ws {
+    /// Sets the [`WebSocketConfig`] used for the connection.
+    pub fn config(mut self, config: WebSocketConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Creates the `101 Switching Protocols` response, and calls `func` with the [`WebSocket`]
+    /// once the connection has been upgraded.
+    ///
+    /// `func` is run on a separate task.
+    pub fn on_upgrade<F, Fut>(self, func: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let Self {
+            on_upgrade,
+            accept,
+            config,
+        } = self;
+
+        let span = tracing::trace_span!("WebSocket connection");
+        upgrade::spawn(on_upgrade, span, move |upgraded| async move {
+            let inner = WebSocketStream::from_raw_socket(upgraded, Role::Server, config).await;
+            func(WebSocket { inner }).await;
+        });
+
+        Response::default()
+            .with_status(StatusCode::SWITCHING_PROTOCOLS)
+            .with_header(header::CONNECTION, CONNECTION_UPGRADE.clone())
+            .with_header(header::UPGRADE, WEBSOCKET.clone())
+            .with_header(header::SEC_WEBSOCKET_ACCEPT, accept)
+    }
+}
+
+impl fmt::Debug for Ws {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ws")
+            .field("accept", &self.accept)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An upgraded WebSocket connection.
+///
+/// This is a [`Stream`] of incoming [`Message`]s, and a [`Sink`] for outgoing [`Message`]s.
+pub struct WebSocket {
+    inner: WebSocketStream<Upgraded>,
+}
+
+impl WebSocket {
+    /// Sends a close frame and closes the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the close frame could not be sent.
+    pub async fn close(mut self, frame: Option<CloseFrame<'static>>) -> Result<(), ProtocolError> {
+        self.inner.close(frame).await
+    }
+}
+
+impl fmt::Debug for WebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket").finish_non_exhaustive()
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Message, ProtocolError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for WebSocket {
+    type Error = ProtocolError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+/// An error for the [`ws()`] filter.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The `Connection` header did not contain `upgrade`.
+    MissingConnectionUpgrade,
+
+    /// The [`Sec-WebSocket-Version`](header::SEC_WEBSOCKET_VERSION) was not `13`.
+    UnsupportedVersion,
+
+    /// The [`Sec-WebSocket-Key`](header::SEC_WEBSOCKET_KEY) was not present.
+    MissingKey,
+
+    /// The underlying connection does not support upgrades, such as for HTTP/2 requests.
+    ConnectionNotUpgradable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingConnectionUpgrade => write!(f, "missing `Connection: upgrade` header"),
+            Self::UnsupportedVersion => write!(f, "unsupported WebSocket version"),
+            Self::MissingKey => write!(f, "missing Sec-WebSocket-Key header"),
+            Self::ConnectionNotUpgradable => write!(f, "connection cannot be upgraded"),
+        }
+    }
+}
+
+impl FilterError for Error {
+    fn into_response(self: Box<Self>) -> Response {
+        tracing::debug!("default response for WebSocket handshake error: {}", self);
+        match *self {
+            Self::UnsupportedVersion => default_response(StatusCode::UPGRADE_REQUIRED)
+                .with_header(header::SEC_WEBSOCKET_VERSION, VERSION_13.clone()),
+            Self::MissingConnectionUpgrade | Self::MissingKey | Self::ConnectionNotUpgradable => {
+                default_response(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ws, Error};
+    use crate::{header, test, Forwarding};
+
+    #[tokio::test]
+    async fn not_websocket() {
+        test::get().not_found(&ws()).await;
+        test::get()
+            .header(header::UPGRADE, "h2c")
+            .header(header::CONNECTION, "Upgrade")
+            .not_found(&ws())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn wrong_method() {
+        let forwarding = test::post()
+            .header(header::UPGRADE, "websocket")
+            .forwarding(&ws())
+            .await;
+        assert!(matches!(forwarding, Forwarding::MethodNotAllowed(_)));
+    }
+
+    #[tokio::test]
+    async fn unsupported_version() {
+        let error: Error = test::get()
+            .header(header::UPGRADE, "WebSocket")
+            .header(header::CONNECTION, "keep-alive, Upgrade")
+            .header(header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+            .header(header::SEC_WEBSOCKET_VERSION, "8")
+            .error(&ws())
+            .await;
+        assert!(matches!(error, Error::UnsupportedVersion));
+    }
+
+    #[tokio::test]
+    async fn missing_key() {
+        let error: Error = test::get()
+            .header(header::UPGRADE, "websocket")
+            .header(header::CONNECTION, "upgrade")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .error(&ws())
+            .await;
+        assert!(matches!(error, Error::MissingKey));
+    }
+
+    #[tokio::test]
+    async fn not_upgradable() {
+        let error: Error = test::get()
+            .header(header::UPGRADE, "websocket")
+            .header(header::CONNECTION, "upgrade")
+            .header(header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .error(&ws())
+            .await;
+        assert!(matches!(error, Error::ConnectionNotUpgradable));
+    }
+}
@@ -0,0 +1,154 @@
+use std::{future::poll_fn, net::SocketAddr, sync::Arc};
+
+use hyper::body::Buf;
+use myth::{server::Error, tls::ClientAuth, version::Version, Filter, PeerAddr, Server, TlsConfig};
+use quinn::{
+    crypto::rustls::QuicClientConfig,
+    rustls::{self, crypto::ring, pki_types::CertificateDer, version::TLS13, RootCertStore},
+};
+use tokio_rustls::rustls::{Certificate, PrivateKey};
+
+/// Sends a `GET` request over HTTP/3, returning the status and body of the response.
+async fn get(addr: SocketAddr, cert: &[u8], path: &str) -> (u16, String) {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(cert.to_vec())).unwrap();
+    let mut config =
+        rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_protocol_versions(&[&TLS13])
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    config.alpn_protocols = vec![b"h3".to_vec()];
+
+    let mut endpoint = quinn::Endpoint::client(([127, 0, 0, 1], 0).into()).unwrap();
+    endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(
+        QuicClientConfig::try_from(config).unwrap(),
+    )));
+    let connection = endpoint.connect(addr, "localhost").unwrap().await.unwrap();
+
+    let (mut driver, mut send_request) = h3::client::new(h3_quinn::Connection::new(connection))
+        .await
+        .unwrap();
+    let drive = tokio::spawn(async move { poll_fn(|cx| driver.poll_close(cx)).await });
+
+    let request = h3_http::Request::get(format!("https://localhost{}", path))
+        .body(())
+        .unwrap();
+    let mut stream = send_request.send_request(request).await.unwrap();
+    stream.finish().await.unwrap();
+    let response = stream.recv_response().await.unwrap();
+    let mut body = Vec::new();
+    while let Some(mut data) = stream.recv_data().await.unwrap() {
+        body.extend_from_slice(&data.copy_to_bytes(data.remaining()));
+    }
+
+    drop(send_request);
+    drive.abort();
+    endpoint.close(0u32.into(), b"");
+    (response.status().as_u16(), String::from_utf8(body).unwrap())
+}
+
+#[tokio::test]
+async fn http3() {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let cert = generated.serialize_der().unwrap();
+    let tls = TlsConfig::new(
+        vec![Certificate(cert.clone())],
+        PrivateKey(generated.serialize_private_key_der()),
+    );
+
+    let filter = myth::path::end()
+        .and(myth::version::version())
+        .and(myth::remote_addr())
+        .handle(|version: Version, remote_addr: PeerAddr| async move {
+            Ok(format!("{:?} {}", version, remote_addr.tcp().is_some()))
+        });
+    let server = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(tls)
+        .and_bind(([127, 0, 0, 1], 0))
+        .and_bind_http3(([127, 0, 0, 1], 0));
+    let http3_addr = server.http3_local_addrs()[0];
+    let handle = server.start();
+    let addrs = handle.local_addr();
+    let (tcp_addr, plain_addr) = (addrs[0].tcp().unwrap(), addrs[1].tcp().unwrap());
+
+    assert_eq!(
+        get(http3_addr, &cert, "/").await,
+        (200, "HTTP/3.0 false".to_owned())
+    );
+    assert_eq!(get(http3_addr, &cert, "/missing").await.0, 404);
+
+    let response = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap()
+        .get(format!("https://localhost:{}/", tcp_addr.port()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["Alt-Svc"],
+        format!("h3=\":{}\"; ma=86400", http3_addr.port()).as_str()
+    );
+    assert_eq!(response.text().await.unwrap(), "HTTP/1.1 true");
+
+    // HTTP/3 is not advertised over plain HTTP.
+    let response = reqwest::get(format!("http://{}/", plain_addr))
+        .await
+        .unwrap();
+    assert!(response.headers().get("Alt-Svc").is_none());
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[test]
+#[should_panic(expected = "HTTP/3 requires a `TlsConfig`")]
+fn http3_without_tls() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+    let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .and_bind_http3(([127, 0, 0, 1], 0));
+}
+
+#[tokio::test]
+async fn http3_unavailable() {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let config = tokio_rustls::rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![Certificate(generated.serialize_der().unwrap())],
+            PrivateKey(generated.serialize_private_key_der()),
+        )
+        .unwrap();
+
+    let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    let result = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(TlsConfig::from(config))
+        .try_and_bind_http3(([127, 0, 0, 1], 0));
+    assert!(matches!(result, Err(Error::Http3Unavailable)));
+}
+
+#[tokio::test]
+async fn http3_client_auth() {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let cert = Certificate(generated.serialize_der().unwrap());
+    let tls = TlsConfig::new(
+        vec![cert.clone()],
+        PrivateKey(generated.serialize_private_key_der()),
+    )
+    .client_auth(ClientAuth::Required, vec![cert]);
+
+    // HTTP/3 would otherwise accept clients without a certificate.
+    let filter = myth::any().handle(|| async { Ok("Hello world!") });
+    let result = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(tls)
+        .try_and_bind_http3(([127, 0, 0, 1], 0));
+    assert!(matches!(result, Err(Error::Http3Unavailable)));
+}
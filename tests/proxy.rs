@@ -0,0 +1,127 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use myth::{
+    proxy::{ProxyConfig, ProxyHeader},
+    server::ServerHandle,
+    Filter, PeerAddr, Server,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+fn start(config: ProxyConfig) -> ServerHandle<SocketAddr> {
+    let filter = myth::remote_addr().and(myth::proxy::header()).handle(
+        |addr: PeerAddr, header: Option<Arc<ProxyHeader>>| async move {
+            let authority = header
+                .as_ref()
+                .and_then(|header| header.authority())
+                .unwrap_or("none")
+                .to_owned();
+            Ok(format!("{} {}", addr, authority))
+        },
+    );
+    Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .proxy_protocol(config)
+        .start()
+}
+
+async fn send(addr: SocketAddr, prefix: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(prefix).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await;
+    response
+}
+
+#[tokio::test]
+async fn v1() {
+    let server = start(ProxyConfig::new());
+    let response = send(
+        server.local_addr(),
+        b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("192.0.2.1:56324 none"));
+    server.shutdown();
+    server.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn v2_with_tlv() {
+    let server = start(ProxyConfig::new());
+    let mut header = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+    header.extend_from_slice(&[0x21, 0x11, 0, 23]);
+    header.extend_from_slice(&[192, 0, 2, 1, 198, 51, 100, 1, 0xdc, 0x04, 0x01, 0xbb]);
+    header.extend_from_slice(&[0x02, 0, 8]);
+    header.extend_from_slice(b"myth.rs:");
+    let response = send(server.local_addr(), &header).await;
+    assert!(response.ends_with("192.0.2.1:56324 myth.rs:"));
+    server.shutdown();
+    server.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn missing_header() {
+    let server = start(ProxyConfig::new());
+    let response = send(server.local_addr(), b"").await;
+    assert_eq!(response, "");
+    server.shutdown();
+    server.wait().await.unwrap();
+
+    let server = start(ProxyConfig::new().required(false));
+    let response = send(server.local_addr(), b"").await;
+    assert!(response.contains("127.0.0.1:"));
+    assert!(response.ends_with(" none"));
+    server.shutdown();
+    server.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn max_pending() {
+    let server = start(
+        ProxyConfig::new()
+            .timeout(Duration::from_millis(200))
+            .max_pending(1),
+    );
+
+    // A client that never sends a header holds the only pending place, so the next client waits
+    // until it is dropped.
+    let mut silent = TcpStream::connect(server.local_addr()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let start = tokio::time::Instant::now();
+    let response = send(
+        server.local_addr(),
+        b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+    )
+    .await;
+    assert!(response.ends_with("192.0.2.1:56324 none"));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(silent.read(&mut [0; 1]).await.unwrap_or(0), 0);
+
+    server.shutdown();
+    server.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn untrusted_source() {
+    let server = start(ProxyConfig::new().trusted(["10.0.0.0/8".parse().unwrap()]));
+    let response = send(
+        server.local_addr(),
+        b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+
+    let response = send(server.local_addr(), b"").await;
+    assert!(response.contains("127.0.0.1:"));
+    assert!(response.ends_with(" none"));
+    server.shutdown();
+    server.wait().await.unwrap();
+}
@@ -0,0 +1,382 @@
+use std::time::Duration;
+
+use myth::{
+    connection::{self, ConnectionInfo},
+    ConnectionLimits, Filter, Http1Config, Http2Config, Server,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::oneshot,
+};
+
+fn hello() -> impl Filter + for<'f> myth::FilterBase<'f, Input = (), Success = (&'static str,)> {
+    myth::any().handle(|| async { Ok("Hello world!") })
+}
+
+#[tokio::test]
+async fn http2_only() {
+    let server = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .http2_only()
+        .http2(Http2Config::new().max_concurrent_streams(10));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let client = reqwest::Client::builder()
+        .http2_prior_knowledge()
+        .build()
+        .unwrap();
+    let response = client.get(format!("http://{}", addr)).send().await.unwrap();
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    assert_eq!(response.text().await.unwrap(), "Hello world!");
+
+    let response = reqwest::Client::builder()
+        .http1_only()
+        .build()
+        .unwrap()
+        .get(format!("http://{}", addr))
+        .send()
+        .await;
+    assert!(response.is_err());
+}
+
+#[test]
+#[should_panic(expected = "the maximum frame size must be between")]
+fn http2_max_frame_size_out_of_range() {
+    Http2Config::new().max_frame_size(1024);
+}
+
+#[tokio::test]
+async fn h2c_and_http1() {
+    let filter =
+        myth::version::version().handle(|version| async move { Ok(format!("{:?}", version)) });
+    let server = Server::new(filter).bind(([127, 0, 0, 1], 0));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let client = reqwest::Client::builder()
+        .http2_prior_knowledge()
+        .build()
+        .unwrap();
+    let response = client.get(format!("http://{}", addr)).send().await.unwrap();
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    assert_eq!(response.text().await.unwrap(), "HTTP/2.0");
+
+    let client = reqwest::Client::builder().http1_only().build().unwrap();
+    let response = client.get(format!("http://{}", addr)).send().await.unwrap();
+    assert_eq!(response.version(), reqwest::Version::HTTP_11);
+    assert_eq!(response.text().await.unwrap(), "HTTP/1.1");
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\n\
+              Upgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut response = [0; 17];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, b"HTTP/1.1 200 OK\r\n");
+}
+
+#[tokio::test]
+async fn http1_without_keep_alive() {
+    let server = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .http1_only()
+        .http1(
+            Http1Config::new()
+                .keep_alive(false)
+                .title_case_headers(true),
+        )
+        .tcp_nodelay(true)
+        .tcp_keepalive(Some(Duration::from_secs(60)));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    // The server closes the connection after the response.
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("\r\nContent-Length: 12\r\n"));
+    assert!(response.ends_with("Hello world!"));
+}
+
+#[tokio::test]
+async fn header_read_timeout() {
+    let server = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .http1(Http1Config::new().header_read_timeout(Duration::from_millis(100)));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("connection should be closed")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn multiple_listeners() {
+    let server = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .and_bind(([127, 0, 0, 1], 0))
+        .and_bind(([127, 0, 0, 1], 0));
+    let addrs = server.local_addr();
+    assert_eq!(addrs.len(), 3);
+
+    let (shutdown, signal) = oneshot::channel();
+    let running = tokio::spawn(server.run_with_graceful_shutdown(async {
+        signal.await.ok();
+    }));
+
+    for addr in addrs.iter() {
+        let body = reqwest::get(format!("http://{}", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "Hello world!");
+    }
+
+    shutdown.send(()).unwrap();
+    running.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn handle_shutdown_timeout() {
+    let (entered, on_entered) = oneshot::channel();
+    let entered = std::sync::Mutex::new(Some(entered));
+    let filter = myth::any().handle(move || {
+        let entered = entered.lock().unwrap().take();
+        async move {
+            if let Some(entered) = entered {
+                entered.send(()).unwrap();
+            }
+            // Never respond.
+            std::future::pending::<()>().await;
+            Ok("")
+        }
+    });
+    let handle = myth::serve(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .shutdown_timeout(Duration::from_millis(100))
+        .start();
+    assert_eq!(handle.connections(), 0);
+
+    let url = format!("http://{}", handle.local_addr());
+    let request = tokio::spawn(reqwest::get(url));
+    on_entered.await.unwrap();
+    assert_eq!(handle.connections(), 1);
+    assert_eq!(handle.requests(), 1);
+
+    handle.shutdown();
+    tokio::time::timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .expect("server should shut down after the timeout")
+        .unwrap();
+    assert!(request.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn handle_graceful_shutdown() {
+    let handle = Server::new(hello()).bind(([127, 0, 0, 1], 0)).start();
+    let body = reqwest::get(format!("http://{}", handle.local_addr()))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert_eq!(body, "Hello world!");
+    assert_eq!(handle.requests(), 0);
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn adopt_listener() {
+    let first = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let second = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addrs = [first.local_addr().unwrap(), second.local_addr().unwrap()];
+
+    let server = Server::new(hello())
+        .from_tcp(first)
+        .and_from_tcp(second)
+        .start();
+    for addr in addrs {
+        let body = reqwest::get(format!("http://{}", addr))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "Hello world!");
+    }
+    server.shutdown();
+    server.wait().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn adopt_fd() {
+    use std::os::unix::io::OwnedFd;
+
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Server::new(hello()).from_fd(OwnedFd::from(listener));
+    assert_eq!(server.local_addr(), addr);
+
+    // A socket that is not a TCP listener cannot be used.
+    let (socket, _) = std::os::unix::net::UnixStream::pair().unwrap();
+    assert!(Server::new(hello())
+        .try_from_fd(OwnedFd::from(socket))
+        .is_err());
+}
+
+async fn get(stream: &mut TcpStream) -> String {
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0; 1024];
+    let len = stream.read(&mut buf).await.unwrap();
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[tokio::test]
+async fn max_connections() {
+    let limits = ConnectionLimits::new().max_connections(1);
+    let metrics = limits.metrics();
+    let handle = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .connection_limits(limits)
+        .start();
+
+    let mut first = TcpStream::connect(handle.local_addr()).await.unwrap();
+    assert!(get(&mut first).await.ends_with("Hello world!"));
+    assert_eq!(metrics.active(), 1);
+
+    // The second connection waits until the first one closes.
+    let mut second = TcpStream::connect(handle.local_addr()).await.unwrap();
+    let response = tokio::spawn(async move { get(&mut second).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!response.is_finished());
+    drop(first);
+    assert!(response.await.unwrap().ends_with("Hello world!"));
+    assert_eq!(metrics.accepted(), 2);
+    assert_eq!(metrics.rejected(), 0);
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+    assert_eq!(metrics.active(), 0);
+}
+
+#[tokio::test]
+async fn max_connections_shared() {
+    // Two servers sharing limits both resume accepting when a connection closes.
+    let limits = ConnectionLimits::new().max_connections(1);
+    let first_handle = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .connection_limits(limits.clone())
+        .start();
+    let second_handle = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .connection_limits(limits)
+        .start();
+
+    let mut held = TcpStream::connect(first_handle.local_addr()).await.unwrap();
+    assert!(get(&mut held).await.ends_with("Hello world!"));
+
+    let mut second = TcpStream::connect(second_handle.local_addr())
+        .await
+        .unwrap();
+    let second = tokio::spawn(async move { get(&mut second).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let mut first = TcpStream::connect(first_handle.local_addr()).await.unwrap();
+    let first = tokio::spawn(async move {
+        let response = get(&mut first).await;
+        drop(first);
+        response
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(held);
+
+    let second = tokio::time::timeout(Duration::from_secs(5), second)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(second.ends_with("Hello world!"));
+    drop(second_handle);
+    let first = tokio::time::timeout(Duration::from_secs(5), first)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(first.ends_with("Hello world!"));
+    first_handle.shutdown();
+}
+
+#[tokio::test]
+async fn max_connections_per_ip() {
+    let limits = ConnectionLimits::new().max_connections_per_ip(1);
+    let metrics = limits.metrics();
+    let handle = Server::new(hello())
+        .bind(([127, 0, 0, 1], 0))
+        .connection_limits(limits)
+        .start();
+
+    let mut first = TcpStream::connect(handle.local_addr()).await.unwrap();
+    assert!(get(&mut first).await.ends_with("Hello world!"));
+
+    let mut second = TcpStream::connect(handle.local_addr()).await.unwrap();
+    let mut buf = [0; 16];
+    assert_eq!(second.read(&mut buf).await.unwrap_or(0), 0);
+    assert_eq!(metrics.rejected(), 1);
+    assert_eq!(metrics.active(), 1);
+
+    drop(first);
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn connection_info() {
+    let filter = connection::info().handle(|info: ConnectionInfo| async move {
+        Ok(format!(
+            "{} {} {}",
+            info.id(),
+            info.local_addr().unwrap(),
+            info.is_tls()
+        ))
+    });
+    let handle = Server::new(filter).bind(([127, 0, 0, 1], 0)).start();
+    let suffix = format!(" {} false", handle.local_addr());
+    let id = |response: &str| {
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        assert!(body.ends_with(&suffix));
+        body.split(' ').next().unwrap().to_owned()
+    };
+
+    // Requests on the same connection share an id.
+    let mut first = TcpStream::connect(handle.local_addr()).await.unwrap();
+    let first_id = id(&get(&mut first).await);
+    assert_eq!(id(&get(&mut first).await), first_id);
+
+    let mut second = TcpStream::connect(handle.local_addr()).await.unwrap();
+    assert_ne!(id(&get(&mut second).await), first_id);
+
+    drop((first, second));
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
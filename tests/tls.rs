@@ -0,0 +1,539 @@
+use std::{convert::TryFrom, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use myth::{
+    connection::{self, ConnectionInfo},
+    security::https_redirect,
+    server::ServerHandle,
+    tls::{self, ClientAuth, PeerCertificate},
+    upgrade::Upgrade,
+    Filter, Responder, Server, TlsConfig,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{self, Certificate, PrivateKey, RootCertStore, ServerName},
+    TlsConnector,
+};
+
+fn generate(name: &str) -> (Certificate, PrivateKey) {
+    let cert = rcgen::generate_simple_self_signed(vec![name.to_owned()]).unwrap();
+    (
+        Certificate(cert.serialize_der().unwrap()),
+        PrivateKey(cert.serialize_private_key_der()),
+    )
+}
+
+fn start(config: TlsConfig) -> ServerHandle<SocketAddr> {
+    let filter = tls::server_name().handle(|name: Option<String>| async move {
+        Ok(name.unwrap_or_else(|| "none".to_owned()))
+    });
+    Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(config)
+        .start()
+}
+
+/// Connects to `addr` with `name` as the server name, returning the certificate sent by the
+/// server and the response body.
+async fn get(addr: SocketAddr, name: &str, roots: &[Certificate]) -> (Certificate, String) {
+    request(addr, name, roots, None).await.unwrap()
+}
+
+/// Connects to `addr` with `name` as the server name, presenting `client_cert` if provided.
+async fn request(
+    addr: SocketAddr,
+    name: &str,
+    roots: &[Certificate],
+    client_cert: Option<(Certificate, PrivateKey)>,
+) -> io::Result<(Certificate, String)> {
+    send(addr, name, client_config(roots, client_cert)).await
+}
+
+fn client_config(
+    roots: &[Certificate],
+    client_cert: Option<(Certificate, PrivateKey)>,
+) -> rustls::ClientConfig {
+    let mut root_store = RootCertStore::empty();
+    for root in roots {
+        root_store.add(root).unwrap();
+    }
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store);
+    match client_cert {
+        Some((cert, key)) => builder.with_single_cert(vec![cert], key).unwrap(),
+        None => builder.with_no_client_auth(),
+    }
+}
+
+/// Sends a request to `addr` with `config`, returning the certificate sent by the server and
+/// the response body.
+async fn send(
+    addr: SocketAddr,
+    name: &str,
+    config: rustls::ClientConfig,
+) -> io::Result<(Certificate, String)> {
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let stream = TcpStream::connect(addr).await?;
+    let server_name = ServerName::try_from(name).unwrap();
+    let mut stream = connector.connect(server_name, stream).await?;
+    let cert = stream.get_ref().1.peer_certificates().unwrap()[0].clone();
+
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let body = response
+        .split("\r\n\r\n")
+        .nth(1)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no response"))?
+        .to_owned();
+    Ok((cert, body))
+}
+
+#[tokio::test]
+async fn sni() {
+    let (exact, exact_key) = generate("a.test");
+    let (wildcard, wildcard_key) = generate("*.b.test");
+    let (default, default_key) = generate("c.test");
+    let config = TlsConfig::sni()
+        .cert("A.test", vec![exact.clone()], exact_key)
+        .cert("*.b.test", vec![wildcard.clone()], wildcard_key)
+        .default_cert(vec![default.clone()], default_key)
+        .build();
+    let handle = start(config);
+    let roots = [exact.clone(), wildcard.clone(), default.clone()];
+
+    let (cert, body) = get(handle.local_addr(), "a.test", &roots).await;
+    assert_eq!(cert, exact);
+    assert_eq!(body, "a.test");
+
+    let (cert, body) = get(handle.local_addr(), "www.b.test", &roots).await;
+    assert_eq!(cert, wildcard);
+    assert_eq!(body, "www.b.test");
+
+    let (cert, body) = get(handle.local_addr(), "c.test", &roots).await;
+    assert_eq!(cert, default);
+    assert_eq!(body, "c.test");
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn silent_client() {
+    let (cert, key) = generate("localhost");
+    let config = TlsConfig::new(vec![cert.clone()], key)
+        .handshake_timeout(Duration::from_millis(200))
+        .max_pending_handshakes(1);
+    let handle = start(config);
+    let addr = handle.local_addr();
+
+    // A client that never sends a `ClientHello` holds the only handshake, so the next client
+    // waits until it is dropped.
+    let mut silent = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let start = tokio::time::Instant::now();
+    let (_, body) = get(addr, "localhost", &[cert]).await;
+    assert_eq!(body, "localhost");
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    let read = tokio::time::timeout(Duration::from_secs(5), silent.read(&mut [0; 1]))
+        .await
+        .unwrap();
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn single_cert() {
+    let (cert, key) = generate("localhost");
+    let handle = start(TlsConfig::new(vec![cert.clone()], key));
+
+    let (received, body) = get(
+        handle.local_addr(),
+        "localhost",
+        std::slice::from_ref(&cert),
+    )
+    .await;
+    assert_eq!(received, cert);
+    assert_eq!(body, "localhost");
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+/// A directory for certificate files, which is removed when dropped.
+struct TempDir(std::path::PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("myth-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Writes a new certificate and key for `localhost`, returning the certificate.
+    fn write_cert(&self) -> Certificate {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let pem = cert.serialize_pem().unwrap();
+        std::fs::write(self.0.join("cert.pem"), &pem).unwrap();
+        std::fs::write(self.0.join("key.pem"), cert.serialize_private_key_pem()).unwrap();
+        // Each serialization is signed again, so read back the one that was written.
+        let der = rustls_pemfile::certs(&mut pem.as_bytes())
+            .unwrap()
+            .remove(0);
+        Certificate(der)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[tokio::test]
+async fn reload() {
+    let dir = TempDir::new("reload");
+    let first = dir.write_cert();
+    let (config, reloader) =
+        TlsConfig::read_file_reloadable(dir.0.join("cert.pem"), dir.0.join("key.pem"));
+    let handle = start(config);
+
+    let (cert, _) = get(
+        handle.local_addr(),
+        "localhost",
+        std::slice::from_ref(&first),
+    )
+    .await;
+    assert_eq!(cert, first);
+
+    let second = dir.write_cert();
+    assert!(reloader.reload());
+    let (cert, _) = get(
+        handle.local_addr(),
+        "localhost",
+        std::slice::from_ref(&second),
+    )
+    .await;
+    assert_eq!(cert, second);
+
+    // A bad reload keeps the previous certificate.
+    std::fs::write(dir.0.join("key.pem"), "not a key").unwrap();
+    assert!(!reloader.reload());
+    let (cert, _) = get(
+        handle.local_addr(),
+        "localhost",
+        std::slice::from_ref(&second),
+    )
+    .await;
+    assert_eq!(cert, second);
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn watch() {
+    let dir = TempDir::new("watch");
+    let first = dir.write_cert();
+    let (config, reloader) =
+        TlsConfig::read_file_reloadable(dir.0.join("cert.pem"), dir.0.join("key.pem"));
+    let handle = start(config);
+    let watch = reloader.watch(Duration::from_millis(20));
+
+    let second = dir.write_cert();
+    let roots = [first, second.clone()];
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while get(handle.local_addr(), "localhost", &roots).await.0 != second {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("the certificate should be reloaded");
+
+    watch.abort();
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+/// Generates a certificate authority, and a certificate for a client named `client` that it
+/// signed.
+fn generate_client() -> (Certificate, Certificate, PrivateKey) {
+    let mut ca_params = rcgen::CertificateParams::new(Vec::new());
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = rcgen::Certificate::from_params(ca_params).unwrap();
+
+    let mut params = rcgen::CertificateParams::new(vec!["client.test".to_owned()]);
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, "client");
+    let cert = rcgen::Certificate::from_params(params).unwrap();
+    (
+        Certificate(ca.serialize_der().unwrap()),
+        Certificate(cert.serialize_der_with_signer(&ca).unwrap()),
+        PrivateKey(cert.serialize_private_key_der()),
+    )
+}
+
+fn start_client_auth(mode: ClientAuth, ca: Certificate) -> (ServerHandle<SocketAddr>, Certificate) {
+    let (cert, key) = generate("localhost");
+    let config = TlsConfig::new(vec![cert.clone()], key).client_auth(mode, vec![ca]);
+    let filter =
+        tls::peer_certificates().handle(|certs: Option<Arc<[PeerCertificate]>>| async move {
+            Ok(match certs.as_deref() {
+                Some([cert, ..]) => format!(
+                    "{} {:?}",
+                    cert.common_name().unwrap_or("none"),
+                    cert.subject_alt_names(),
+                ),
+                _ => "anonymous".to_owned(),
+            })
+        });
+    let handle = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(config)
+        .start();
+    (handle, cert)
+}
+
+#[tokio::test]
+async fn client_auth_required() {
+    let (ca, client_cert, client_key) = generate_client();
+    let (handle, cert) = start_client_auth(ClientAuth::Required, ca);
+    let roots = std::slice::from_ref(&cert);
+
+    let (_, body) = request(
+        handle.local_addr(),
+        "localhost",
+        roots,
+        Some((client_cert, client_key)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, r#"client [Dns("client.test")]"#);
+
+    assert!(request(handle.local_addr(), "localhost", roots, None)
+        .await
+        .is_err());
+
+    let (other, other_key) = generate("client.test");
+    assert!(request(
+        handle.local_addr(),
+        "localhost",
+        roots,
+        Some((other, other_key))
+    )
+    .await
+    .is_err());
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn client_auth_optional() {
+    let (ca, client_cert, client_key) = generate_client();
+    let (handle, cert) = start_client_auth(ClientAuth::Optional, ca);
+    let roots = std::slice::from_ref(&cert);
+
+    let (_, body) = request(
+        handle.local_addr(),
+        "localhost",
+        roots,
+        Some((client_cert, client_key)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, r#"client [Dns("client.test")]"#);
+
+    let (_, body) = get(handle.local_addr(), "localhost", roots).await;
+    assert_eq!(body, "anonymous");
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[test]
+fn try_read_file_errors() {
+    let dir = TempDir::new("errors");
+    let missing = dir.0.join("missing.pem");
+    match TlsConfig::try_read_file(&missing, &missing) {
+        Err(tls::Error::Io { path, .. }) => assert_eq!(path, missing),
+        other => panic!("expected an I/O error, got {:?}", other.err()),
+    }
+
+    dir.write_cert();
+    let cert_path = dir.0.join("cert.pem");
+    assert!(matches!(
+        TlsConfig::try_read_file(&cert_path, &cert_path),
+        Err(tls::Error::NoKey)
+    ));
+
+    let combined = format!(
+        "{}{}",
+        std::fs::read_to_string(&cert_path).unwrap(),
+        std::fs::read_to_string(dir.0.join("key.pem")).unwrap()
+    );
+    let combined_path = dir.0.join("combined.pem");
+    std::fs::write(&combined_path, combined).unwrap();
+    TlsConfig::try_read_combined_file(&combined_path).unwrap();
+    TlsConfig::try_read_file(&combined_path, &combined_path).unwrap();
+
+    dir.write_cert();
+    assert!(matches!(
+        TlsConfig::try_read_file(&combined_path, dir.0.join("key.pem")),
+        Err(tls::Error::MismatchedKey)
+    ));
+}
+
+#[tokio::test]
+async fn connection_info() {
+    let (cert, key) = generate("localhost");
+    let filter = connection::info().handle(|info: ConnectionInfo| async move {
+        let tls = info.tls().unwrap();
+        Ok(format!(
+            "{} {:?} {:?} {} {}",
+            info.local_addr().unwrap(),
+            info.http_version(),
+            tls.protocol_version().unwrap(),
+            String::from_utf8_lossy(tls.alpn_protocol().unwrap()),
+            tls.server_name().unwrap(),
+        ))
+    });
+    let handle = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(TlsConfig::new(vec![cert.clone()], key))
+        .start();
+
+    let mut config = client_config(std::slice::from_ref(&cert), None);
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let (_, body) = send(handle.local_addr(), "localhost", config)
+        .await
+        .unwrap();
+    assert_eq!(
+        body,
+        format!(
+            "{} HTTP/1.1 TLSv1_3 http/1.1 localhost",
+            handle.local_addr()
+        )
+    );
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn redirect_to_https() {
+    let (cert, key) = generate("localhost");
+    let app = myth::any().handle(|| async { Ok("Hello world!".into_response()) });
+    let filter = https_redirect(https_redirect::Config::new().port(8443)).or(app);
+    let handle = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(TlsConfig::new(vec![cert.clone()], key))
+        .and_bind(([127, 0, 0, 1], 0))
+        .start();
+    let addrs = handle.local_addr();
+    let (https, http) = (addrs[0].tcp().unwrap(), addrs[1].tcp().unwrap());
+
+    let (_, body) = get(https, "localhost", std::slice::from_ref(&cert)).await;
+    assert_eq!(body, "Hello world!");
+
+    let mut stream = TcpStream::connect(http).await.unwrap();
+    stream
+        .write_all(b"GET /path?query HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 308 Permanent Redirect\r\n"));
+    assert!(response
+        .to_ascii_lowercase()
+        .contains("\r\nlocation: https://localhost:8443/path?query\r\n"));
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[cfg(feature = "self-signed")]
+#[tokio::test]
+async fn self_signed() {
+    let (config, self_signed) = TlsConfig::self_signed(["localhost", "127.0.0.1"]);
+    let handle = start(config);
+    let port = handle.local_addr().port();
+
+    let ca = reqwest::Certificate::from_pem(self_signed.ca_cert_pem().as_bytes()).unwrap();
+    let client = reqwest::Client::builder()
+        .add_root_certificate(ca)
+        .build()
+        .unwrap();
+    for host in ["localhost", "127.0.0.1"] {
+        let response = client
+            .get(format!("https://{}:{}", host, port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let dir = std::env::temp_dir().join(format!("myth-self-signed-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    self_signed
+        .write(dir.join("cert.pem"), dir.join("key.pem"))
+        .unwrap();
+    TlsConfig::try_read_file(dir.join("cert.pem"), dir.join("key.pem")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    handle.shutdown();
+    handle.wait().await.unwrap();
+}
+
+#[tokio::test]
+async fn upgrade() {
+    let (cert, key) = generate("localhost");
+    let filter = myth::upgrade::on("echo").handle(|upgrade: Upgrade| async move {
+        Ok(upgrade.on_upgrade(|mut io| async move {
+            let mut buf = [0; 64];
+            while let Ok(len @ 1..) = io.read(&mut buf).await {
+                if io.write_all(&buf[..len]).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    });
+    let handle = Server::new(filter)
+        .bind(([127, 0, 0, 1], 0))
+        .with_tls(TlsConfig::new(vec![cert.clone()], key))
+        .start();
+
+    let connector = TlsConnector::from(Arc::new(client_config(&[cert], None)));
+    let stream = TcpStream::connect(handle.local_addr()).await.unwrap();
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let mut stream = BufReader::new(connector.connect(server_name, stream).await.unwrap());
+    stream
+        .write_all(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: upgrade\r\nUpgrade: echo\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    assert_eq!(line.trim_end(), "HTTP/1.1 101 Switching Protocols");
+    while line != "\r\n" {
+        line.clear();
+        stream.read_line(&mut line).await.unwrap();
+    }
+
+    stream.write_all(b"ping").await.unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+}
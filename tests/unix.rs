@@ -0,0 +1,92 @@
+#![cfg(unix)]
+
+use std::{
+    os::unix::{fs::PermissionsExt, net::UnixListener},
+    path::PathBuf,
+};
+
+use myth::{service::UnixIncoming, Filter, PeerAddr, Server, UnixConfig};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+};
+
+fn socket_path() -> PathBuf {
+    std::env::temp_dir().join(format!("myth-{}.sock", rand::random::<u64>()))
+}
+
+async fn get(path: &PathBuf) -> String {
+    let mut stream = UnixStream::connect(path).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn serve_unix() {
+    let path = socket_path();
+    // Leave a stale socket file behind.
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let filter = myth::remote_addr().handle(|addr: PeerAddr| async move { Ok(addr.to_string()) });
+    let server: Server<UnixIncoming, _> =
+        myth::serve(filter).bind_unix(UnixConfig::new(&path).mode(0o600));
+    assert_eq!(server.local_addr(), PeerAddr::Unix(Some(path.clone())));
+    assert_eq!(path.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+    tokio::spawn(server.run());
+
+    let response = get(&path).await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("unix:(unnamed)"));
+}
+
+#[tokio::test]
+async fn socket_in_use() {
+    let path = socket_path();
+    let filter = myth::any().handle(|| async { Ok("Hello!") });
+    let server = myth::serve(filter).bind_unix(path.as_path());
+    tokio::spawn(server.run());
+
+    let filter = myth::any().handle(|| async { Ok("Hello!") });
+    assert!(myth::serve(filter).try_bind_unix(path.as_path()).is_err());
+    assert!(get(&path).await.ends_with("Hello!"));
+}
+
+#[tokio::test]
+async fn remove_socket_on_drop() {
+    let path = socket_path();
+    let filter = myth::any().handle(|| async { Ok("Hello!") });
+    let server = myth::serve(filter).bind_unix(path.as_path());
+    assert!(path.exists());
+    drop(server);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn mode_without_window() {
+    let path = socket_path();
+    let filter =
+        myth::connection::info().handle(|info: myth::connection::ConnectionInfo| async move {
+            Ok(info.local_addr().unwrap().to_string())
+        });
+    let server = myth::serve(filter).bind_unix(UnixConfig::new(&path).mode(0o660));
+    assert_eq!(path.metadata().unwrap().permissions().mode() & 0o777, 0o660);
+    // The private directory that the socket was bound in is removed.
+    let parent = path.parent().unwrap();
+    let file_name = path.file_name().unwrap().to_str().unwrap();
+    assert!(!std::fs::read_dir(parent).unwrap().any(|entry| {
+        let name = entry.unwrap().file_name();
+        let name = name.to_string_lossy();
+        name.starts_with('.') && name.contains(file_name)
+    }));
+    tokio::spawn(server.run());
+
+    // Accepted connections report the configured path, not the private one.
+    let response = get(&path).await;
+    assert!(response.ends_with(&format!("unix:{}", path.display())));
+}
@@ -0,0 +1,108 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use myth::{upgrade::Upgrade, Filter, StatusCode};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+fn echo(upgrade: Upgrade) -> myth::Response {
+    upgrade.on_upgrade(|mut io| async move {
+        let mut buf = [0; 64];
+        while let Ok(len @ 1..) = io.read(&mut buf).await {
+            if io.write_all(&buf[..len]).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+async fn connect_and_echo(request: &str, status_line: &str) {
+    let filter = myth::upgrade::on("echo")
+        .or(myth::upgrade::connect())
+        .handle(|upgrade| async move { Ok(echo(upgrade)) });
+    let server = myth::serve(filter).bind(([127, 0, 0, 1], 0));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut line = String::new();
+    stream.read_line(&mut line).await.unwrap();
+    assert_eq!(line.trim_end(), status_line);
+    while line != "\r\n" {
+        line.clear();
+        stream.read_line(&mut line).await.unwrap();
+    }
+
+    stream.write_all(b"ping").await.unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+}
+
+#[tokio::test]
+async fn upgrade_protocol() {
+    connect_and_echo(
+        "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: upgrade\r\nUpgrade: echo\r\n\r\n",
+        "HTTP/1.1 101 Switching Protocols",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn connect_method() {
+    connect_and_echo(
+        "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n",
+        "HTTP/1.1 200 OK",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn rejected_upgrade() {
+    let upgraded = Arc::new(AtomicBool::new(false));
+    let filter = myth::upgrade::on("echo").handle({
+        let upgraded = Arc::clone(&upgraded);
+        move |upgrade: Upgrade| {
+            let upgraded = Arc::clone(&upgraded);
+            async move {
+                Ok(upgrade.on_upgrade_with(
+                    (StatusCode::FORBIDDEN, "Forbidden"),
+                    move |_| async move {
+                        upgraded.store(true, Ordering::SeqCst);
+                    },
+                ))
+            }
+        }
+    });
+    let server = myth::serve(filter).bind(([127, 0, 0, 1], 0));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    // The connection is not upgraded, so it can be used for another request.
+    let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    for _ in 0..2 {
+        stream
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: upgrade\r\nUpgrade: echo\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        assert_eq!(line.trim_end(), "HTTP/1.1 403 Forbidden");
+        while line != "\r\n" {
+            line.clear();
+            stream.read_line(&mut line).await.unwrap();
+        }
+        let mut body = [0; 9];
+        stream.read_exact(&mut body).await.unwrap();
+        assert_eq!(&body, b"Forbidden");
+    }
+    assert!(!upgraded.load(Ordering::SeqCst));
+}
@@ -0,0 +1,40 @@
+use futures_util::{SinkExt, StreamExt};
+use myth::{
+    ws::{Message, Ws},
+    Filter,
+};
+
+#[tokio::test]
+async fn echo() {
+    let filter = myth::ws::ws().handle(|ws: Ws| async move {
+        Ok(ws.on_upgrade(|mut socket| async move {
+            while let Some(Ok(message)) = socket.next().await {
+                if message.is_close() || socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    });
+    let server = myth::serve(filter).bind(([127, 0, 0, 1], 0));
+    let addr = server.local_addr();
+    tokio::spawn(server.run());
+
+    let (mut socket, response) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 101);
+
+    socket.send(Message::text("hello")).await.unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::text("hello")
+    );
+
+    socket.send(Message::binary(vec![1, 2, 3])).await.unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::binary(vec![1, 2, 3])
+    );
+
+    socket.close(None).await.unwrap();
+}